@@ -0,0 +1,67 @@
+//! Bulk UTF64 to UTF-8 decoding and validation against a per-unit baseline.
+//!
+//! Run with `cargo bench --bench decode`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use utf_64::{String64, decode_unit, validate_units};
+
+/// Builds roughly 64 KiB of text by repeating `sample`.
+fn corpus(sample: &str) -> String64 {
+    String64::from(sample.repeat(64 * 1024 / sample.len()))
+}
+
+/// The straightforward decoder: decode every unit to a `char`, then re-encode it.
+fn decode_per_unit(units: &[u64]) -> String {
+    let mut decoded = String::new();
+    for &unit in units {
+        decoded.push(decode_unit(unit).expect("valid unit"));
+    }
+    decoded
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let inputs = [
+        (
+            "ascii",
+            corpus("The quick brown fox jumps over the lazy dog. "),
+        ),
+        (
+            "latin",
+            corpus("Größenwahn, déjà vu und Smørrebrød zum Frühstück. "),
+        ),
+        ("cjk", corpus("日本語のテキストと中文文本和한국어 텍스트。")),
+        ("emoji", corpus("🦀🚀🌍✨🎉")),
+    ];
+
+    let mut group = c.benchmark_group("decode");
+    for (name, text) in &inputs {
+        group.throughput(Throughput::Bytes(text.len() as u64 * 8));
+        group.bench_with_input(BenchmarkId::new("bulk", name), text, |b, text| {
+            b.iter(|| black_box(text).to_string())
+        });
+        group.bench_with_input(BenchmarkId::new("per_unit", name), text, |b, text| {
+            b.iter(|| decode_per_unit(black_box(text.as_slice())))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("validate");
+    for (name, text) in &inputs {
+        group.throughput(Throughput::Bytes(text.len() as u64 * 8));
+        group.bench_with_input(BenchmarkId::new("bulk", name), text, |b, text| {
+            b.iter(|| validate_units(black_box(text.as_slice())))
+        });
+        group.bench_with_input(BenchmarkId::new("per_unit", name), text, |b, text| {
+            b.iter(|| {
+                black_box(text.as_slice())
+                    .iter()
+                    .all(|&u| decode_unit(u).is_ok())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+//! Bulk UTF-8 to UTF64 encoding against a per-character baseline.
+//!
+//! Run with `cargo bench --bench encode`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use utf_64::{String64, encode_code_point};
+
+/// Builds roughly 64 KiB of text by repeating `sample`.
+fn corpus(sample: &str) -> String {
+    sample.repeat(64 * 1024 / sample.len())
+}
+
+/// The straightforward encoder: decode every character, then encode it on its own.
+fn encode_per_char(s: &str) -> Vec<u64> {
+    s.chars()
+        .map(|ch| encode_code_point(ch as u32).expect("char is a scalar value"))
+        .collect()
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let inputs = [
+        (
+            "ascii",
+            corpus("The quick brown fox jumps over the lazy dog. "),
+        ),
+        (
+            "latin",
+            corpus("Größenwahn, déjà vu und Smørrebrød zum Frühstück. "),
+        ),
+        ("cjk", corpus("日本語のテキストと中文文本和한국어 텍스트。")),
+        ("emoji", corpus("🦀🚀🌍✨🎉")),
+    ];
+
+    let mut group = c.benchmark_group("encode");
+    for (name, text) in &inputs {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new("bulk", name), text, |b, text| {
+            b.iter(|| String64::from(black_box(text.as_str())))
+        });
+        group.bench_with_input(BenchmarkId::new("per_char", name), text, |b, text| {
+            b.iter(|| encode_per_char(black_box(text)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode);
+criterion_main!(benches);
@@ -0,0 +1,302 @@
+//! A fixed-capacity UTF64 string that needs no allocator.
+
+use crate::error::CapacityError;
+#[cfg(feature = "alloc")]
+use crate::error::Utf64Error;
+use crate::str64::Str64;
+#[cfg(feature = "alloc")]
+use crate::string64::String64;
+use crate::unit::{decode_lossy, encode_char};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+/// A UTF64 string stored inline, holding at most `N` characters.
+///
+/// Since every character is one unit, the capacity is a character count and the
+/// string occupies `8 * N` bytes plus its length, wherever it lives. It dereferences to
+/// [`Str64`] for indexing, searching and iteration.
+///
+/// Operations that would exceed the capacity fail with [`CapacityError`] and leave the
+/// string unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::ArrayString64;
+///
+/// let mut s = ArrayString64::<8>::new();
+/// s.try_push_str("Hello").unwrap();
+/// s.try_push('!').unwrap();
+/// assert_eq!(s, "Hello!");
+/// assert_eq!(s[1], 'e');
+///
+/// assert!(s.try_push_str(", 世界").is_err());
+/// assert_eq!(s, "Hello!");
+/// ```
+#[derive(Clone, Copy)]
+pub struct ArrayString64<const N: usize> {
+    units: [u64; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayString64<N> {
+    /// Creates a new empty `ArrayString64`.
+    pub const fn new() -> Self {
+        Self {
+            units: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the maximum number of characters this string can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the string holds `N` characters.
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the number of characters that can still be appended.
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    /// Appends a character to the end of this string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CapacityError`] if the string is full.
+    pub fn try_push(&mut self, ch: char) -> Result<(), CapacityError> {
+        self.try_push_units(&[encode_char(ch)])
+    }
+
+    /// Appends a string slice to the end of this string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CapacityError`] if `s` has more characters than remain; nothing is
+    /// appended in that case.
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), CapacityError> {
+        let start = self.len;
+        for ch in s.chars() {
+            if self.len == N {
+                // Units past `len` are never read, so rolling back is all it takes
+                self.len = start;
+                return Err(CapacityError::new(N));
+            }
+            self.units[self.len] = encode_char(ch);
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Removes the last character and returns it, or `None` if the string is empty.
+    ///
+    /// An invalid unit is returned as U+FFFD REPLACEMENT CHARACTER.
+    pub fn pop(&mut self) -> Option<char> {
+        self.len = self.len.checked_sub(1)?;
+        Some(decode_lossy(self.units[self.len]))
+    }
+
+    /// Shortens this string to `new_len` characters.
+    ///
+    /// Has no effect if `new_len` is greater than the string's current length.
+    pub fn truncate(&mut self, new_len: usize) {
+        self.len = self.len.min(new_len);
+    }
+
+    /// Removes all characters.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns a `Str64` slice covering the whole string.
+    pub fn as_str64(&self) -> &Str64 {
+        Str64::from_raw(&self.units[..self.len])
+    }
+
+    /// Decodes this UTF64 string back to a standard Rust String.
+    ///
+    /// See [`Str64::to_string`].
+    #[cfg(feature = "alloc")]
+    pub fn to_string(&self) -> crate::Result<String> {
+        self.as_str64().to_string()
+    }
+
+    /// Copies units onto the end, all or nothing.
+    fn try_push_units(&mut self, units: &[u64]) -> Result<(), CapacityError> {
+        let end = self.len + units.len();
+        if end > N {
+            return Err(CapacityError::new(N));
+        }
+        self.units[self.len..end].copy_from_slice(units);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for ArrayString64<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for ArrayString64<N> {
+    type Error = CapacityError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let mut array = Self::new();
+        array.try_push_str(s)?;
+        Ok(array)
+    }
+}
+
+/// Copies the units as they are, like `String64::from(&Str64)`.
+impl<const N: usize> TryFrom<&Str64> for ArrayString64<N> {
+    type Error = CapacityError;
+
+    fn try_from(s: &Str64) -> Result<Self, Self::Error> {
+        let mut array = Self::new();
+        array.try_push_units(s.as_slice())?;
+        Ok(array)
+    }
+}
+
+impl<const N: usize> FromStr for ArrayString64<N> {
+    type Err = CapacityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl<const N: usize> fmt::Display for ArrayString64<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str64(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for ArrayString64<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str64().fmt_debug("ArrayString64", f)
+    }
+}
+
+/// A piece that does not fit fails to write, leaving the string as it was before that
+/// piece; pieces of the same `write!` that came earlier stay written.
+impl<const N: usize> fmt::Write for ArrayString64<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+
+    fn write_char(&mut self, ch: char) -> fmt::Result {
+        self.try_push(ch).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> PartialEq for ArrayString64<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str64() == other.as_str64()
+    }
+}
+
+impl<const N: usize> Eq for ArrayString64<N> {}
+
+impl<const N: usize> Hash for ArrayString64<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<const N: usize> PartialOrd for ArrayString64<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for ArrayString64<N> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str64().cmp(other.as_str64())
+    }
+}
+
+impl<const N: usize> PartialEq<str> for ArrayString64<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str64() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for ArrayString64<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.eq(*other)
+    }
+}
+
+impl<const N: usize> PartialEq<Str64> for ArrayString64<N> {
+    fn eq(&self, other: &Str64) -> bool {
+        self.as_str64() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&Str64> for ArrayString64<N> {
+    fn eq(&self, other: &&Str64) -> bool {
+        self.as_str64() == *other
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<String64> for ArrayString64<N> {
+    fn eq(&self, other: &String64) -> bool {
+        self.as_str64() == other.as_str64()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<ArrayString64<N>> for String64 {
+    fn from(s: ArrayString64<N>) -> Self {
+        String64::from(s.as_str64())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> TryFrom<ArrayString64<N>> for String {
+    type Error = Utf64Error;
+
+    fn try_from(value: ArrayString64<N>) -> crate::Result<Self> {
+        value.to_string()
+    }
+}
+
+impl<const N: usize> AsRef<[u64]> for ArrayString64<N> {
+    fn as_ref(&self) -> &[u64] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> AsRef<Str64> for ArrayString64<N> {
+    fn as_ref(&self) -> &Str64 {
+        self.as_str64()
+    }
+}
+
+impl<const N: usize> Deref for ArrayString64<N> {
+    type Target = Str64;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str64()
+    }
+}
+
+impl<const N: usize> DerefMut for ArrayString64<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Str64::from_raw_mut(&mut self.units[..self.len])
+    }
+}
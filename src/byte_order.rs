@@ -0,0 +1,106 @@
+//! Byte order handling for serialized UTF64 data (SPECIFICATION §8).
+
+#[cfg(feature = "alloc")]
+use crate::error::{ErrorKind, Result, Utf64Error};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// The UTF64 byte order mark, as defined by SPECIFICATION §8.2.
+///
+/// Serialized big-endian it reads `00 00 FE FF 00 00 00 00`; little-endian it reads
+/// `00 00 00 00 FF FE 00 00`.
+pub const BOM: u64 = 0x0000FEFF_00000000;
+
+/// The unit encoding U+FEFF ZERO WIDTH NO-BREAK SPACE.
+///
+/// Some writers emit this character instead of [`BOM`], so it is recognised as a byte
+/// order mark as well.
+const FEFF_UNIT: u64 = 0xEFBBBF00_00000000;
+
+/// The order in which the octets of each UTF64 unit are serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Most significant octet first (network byte order, the UTF64 default).
+    BigEndian,
+    /// Least significant octet first.
+    LittleEndian,
+}
+
+impl ByteOrder {
+    /// The byte order of the target platform.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: ByteOrder = ByteOrder::BigEndian;
+
+    /// The byte order of the target platform.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: ByteOrder = ByteOrder::LittleEndian;
+
+    /// Detects the byte order from a leading byte order mark.
+    ///
+    /// Returns `None` if `bytes` does not start with a BOM in either byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::ByteOrder;
+    ///
+    /// let bytes = [0, 0, 0xFE, 0xFF, 0, 0, 0, 0, 0x41, 0, 0, 0, 0, 0, 0, 0];
+    /// assert_eq!(ByteOrder::from_bom(&bytes), Some(ByteOrder::BigEndian));
+    /// assert_eq!(ByteOrder::from_bom(&bytes[8..]), None);
+    /// ```
+    pub fn from_bom(bytes: &[u8]) -> Option<ByteOrder> {
+        let head: [u8; 8] = bytes.get(..8)?.try_into().ok()?;
+        [ByteOrder::BigEndian, ByteOrder::LittleEndian]
+            .into_iter()
+            .find(|&order| matches!(order.read_unit(head), BOM | FEFF_UNIT))
+    }
+
+    /// Reads one unit serialized in this byte order.
+    pub(crate) fn read_unit(self, bytes: [u8; 8]) -> u64 {
+        match self {
+            ByteOrder::BigEndian => u64::from_be_bytes(bytes),
+            ByteOrder::LittleEndian => u64::from_le_bytes(bytes),
+        }
+    }
+
+    /// Serializes one unit in this byte order.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn write_unit(self, unit: u64) -> [u8; 8] {
+        match self {
+            ByteOrder::BigEndian => unit.to_be_bytes(),
+            ByteOrder::LittleEndian => unit.to_le_bytes(),
+        }
+    }
+}
+
+/// Splits serialized bytes into raw units without validating them.
+///
+/// Fails if `bytes` ends with an incomplete unit.
+#[cfg(feature = "alloc")]
+pub(crate) fn read_units(bytes: &[u8], order: ByteOrder) -> Result<Vec<u64>> {
+    let chunks = bytes.chunks_exact(8);
+    let remainder = chunks.remainder().len();
+    if remainder != 0 {
+        return Err(Utf64Error::new(
+            ErrorKind::IncompleteUnit {
+                len: remainder as u8,
+            },
+            bytes.len() / 8,
+            0,
+        ));
+    }
+
+    Ok(chunks
+        .map(|chunk| order.read_unit(chunk.try_into().expect("chunk of 8 bytes")))
+        .collect())
+}
+
+/// Serializes units in the given byte order.
+#[cfg(feature = "alloc")]
+pub(crate) fn write_units(units: &[u64], order: ByteOrder) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(units.len() * 8);
+    for &unit in units {
+        bytes.extend_from_slice(&order.write_unit(unit));
+    }
+    bytes
+}
@@ -0,0 +1,19 @@
+//! `bytemuck` support, enabled by the `bytemuck` feature.
+//!
+//! [`Char64`] is plain old data: it has the layout of `u64` and holds whatever bits its
+//! buffer holds, so slices of units can be cast to and from bytes without copying. A
+//! cast proves nothing about validity; use [`Str64::from_units`](crate::Str64::from_units)
+//! or [`Str64::from_bytes_ne`](crate::Str64::from_bytes_ne) to view the result as text.
+
+use crate::char64::Char64;
+use bytemuck::{Pod, TransparentWrapper, Zeroable};
+
+// SAFETY: `Char64` is `repr(transparent)` over `u64`, for which all zeroes is valid.
+unsafe impl Zeroable for Char64 {}
+
+// SAFETY: `Char64` is `repr(transparent)` over `u64`, which is `Pod`, and is `Copy`
+// with no padding.
+unsafe impl Pod for Char64 {}
+
+// SAFETY: `Char64` is `repr(transparent)` over `u64`.
+unsafe impl TransparentWrapper<u64> for Char64 {}
@@ -0,0 +1,146 @@
+use crate::error::Utf64Error;
+use crate::unit::{decode_lossy, decode_unit, encode_char};
+use core::{cmp::Ordering, fmt};
+
+/// A single UTF64 character unit.
+///
+/// `Char64` is to [`String64`](crate::String64) what `char` is to `String`: one
+/// fixed-width 64-bit unit with the UTF-8 encoding of a character in its upper half
+/// and the reserved bits in its lower half. It has the same layout as `u64`.
+///
+/// Values built through [`TryFrom<u64>`] or [`From<char>`] always hold a valid v1.0
+/// unit; for `'\0'` that is the all-zero unit. Units viewed through a `String64`
+/// reflect its buffer as-is, so a unit that was corrupted through raw `u64` access
+/// decodes as U+FFFD REPLACEMENT CHARACTER.
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::Char64;
+///
+/// let euro = Char64::from('€');
+/// assert_eq!(euro.code_point(), 0x20AC);
+/// assert_eq!(euro.utf8_len(), 3);
+/// assert_eq!(euro.utf8_bytes(), [0xE2, 0x82, 0xAC, 0x00]);
+/// assert_eq!(u64::from(euro), 0xE282AC00_00000000);
+/// assert_eq!(char::from(euro), '€');
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Char64(u64);
+
+impl Char64 {
+    /// Returns the character this unit encodes.
+    pub fn to_char(self) -> char {
+        decode_lossy(self.0)
+    }
+
+    /// Returns the Unicode code point this unit encodes.
+    ///
+    /// Like [`to_char`](Self::to_char), this is lossy: an invalid unit gives U+FFFD.
+    /// Use [`TryFrom<u64>`] or [`decode_unit`] to detect invalid units.
+    pub fn code_point(self) -> u32 {
+        self.to_char() as u32
+    }
+
+    /// Returns the upper half of the unit as UTF-8 octets.
+    ///
+    /// For a valid unit, only the first [`utf8_len`](Self::utf8_len) octets are
+    /// meaningful; the rest are zero padding. The octets are returned as stored, even
+    /// if the unit is invalid, while `utf8_len` is lossy, so slicing one by the other
+    /// only gives the character's encoding for a valid unit. Check the unit with
+    /// [`TryFrom<u64>`] first, or encode [`to_char`](Self::to_char) instead.
+    pub fn utf8_bytes(self) -> [u8; 4] {
+        ((self.0 >> 32) as u32).to_be_bytes()
+    }
+
+    /// Returns the number of octets in the UTF-8 encoding of this character.
+    ///
+    /// Lossy like [`code_point`](Self::code_point): an invalid unit gives the length of
+    /// U+FFFD, 3, which has nothing to do with the octets
+    /// [`utf8_bytes`](Self::utf8_bytes) returns for it.
+    pub fn utf8_len(self) -> usize {
+        self.to_char().len_utf8()
+    }
+
+    /// Returns the raw reserved lower half of the unit.
+    ///
+    /// Always zero for a valid UTF64 v1.0 unit.
+    pub fn reserved_bits(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Returns the raw 64-bit unit.
+    pub fn to_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Views a raw unit as a `Char64`.
+    pub(crate) fn from_unit_ref(unit: &u64) -> &Char64 {
+        // SAFETY: `Char64` is `repr(transparent)` over `u64`.
+        unsafe { &*(unit as *const u64).cast::<Char64>() }
+    }
+}
+
+impl TryFrom<u64> for Char64 {
+    type Error = Utf64Error;
+
+    fn try_from(unit: u64) -> Result<Self, Self::Error> {
+        decode_unit(unit).map(|_| Char64(unit))
+    }
+}
+
+impl From<char> for Char64 {
+    fn from(ch: char) -> Self {
+        Char64(encode_char(ch))
+    }
+}
+
+impl From<Char64> for char {
+    fn from(ch: Char64) -> Self {
+        ch.to_char()
+    }
+}
+
+impl From<Char64> for u64 {
+    fn from(ch: Char64) -> Self {
+        ch.0
+    }
+}
+
+impl PartialOrd for Char64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Char64 {
+    /// Orders units by code point.
+    ///
+    /// UTF-8 preserves code point order and the encoding is left-aligned, so comparing
+    /// the raw units gives the same order without decoding.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialEq<char> for Char64 {
+    fn eq(&self, other: &char) -> bool {
+        self.0 == encode_char(*other)
+    }
+}
+
+impl fmt::Display for Char64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_char(), f)
+    }
+}
+
+impl fmt::Debug for Char64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match decode_unit(self.0) {
+            Ok(ch) => write!(f, "Char64({ch:?})"),
+            Err(_) => write!(f, "Char64(<invalid {:#018X}>)", self.0),
+        }
+    }
+}
@@ -1,35 +1,254 @@
-use std::fmt;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
 
 /// Errors that can occur during UTF64 encoding and decoding operations.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Utf64Error {
-    /// The input contains invalid UTF-8 data that cannot be encoded to UTF64.
-    InvalidUtf8,
-
-    /// The UTF64 data is malformed or contains invalid sequences.
-    InvalidUtf64,
+///
+/// Besides classifying the failure, the error records where it happened and which
+/// unit was responsible, as recommended by SPECIFICATION §7.2.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::BorrowMut;
+/// use utf_64::{ErrorKind, String64};
+///
+/// let mut s = String64::from("abc");
+/// let units: &mut [u64] = s.borrow_mut();
+/// units[1] |= 1; // corrupt the reserved bits of 'b'
+///
+/// let err = s.to_string().unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::NonZeroReserved);
+/// assert_eq!(err.valid_up_to(), 1);
+/// assert_eq!(err.unit(), 0x6200000000000001);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf64Error {
+    kind: ErrorKind,
+    index: usize,
+    unit: u64,
+}
 
+/// The specific reason a UTF64 unit was rejected.
+///
+/// New kinds may be added in minor releases, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
     /// Reserved bits are not zero (violates UTF64 v1.0 specification).
     ///
     /// The UTF64 v1.0 specification requires the lower 32 bits of each character to be zero.
     /// This error indicates data that may be from a future UTF64 specification version,
     /// or corrupted data.
-    NonZeroReservedBits,
+    NonZeroReserved,
+
+    /// The first octet of the upper half is zero, but the unit is not the all-zero
+    /// unit that encodes U+0000.
+    ZeroLeadByte,
+
+    /// The first octet is not a valid UTF-8 lead byte.
+    InvalidLeadByte(u8),
+
+    /// An octet that should continue the UTF-8 sequence is not of the form `10xxxxxx`.
+    BadContinuation {
+        /// Position of the offending octet within the upper half (1–3).
+        octet: u8,
+        /// The value found at that position.
+        found: u8,
+    },
+
+    /// The UTF-8 sequence encodes the given code point using more octets than needed.
+    Overlong(u32),
+
+    /// The UTF-8 sequence encodes a surrogate code point (U+D800 to U+DFFF).
+    Surrogate(u32),
+
+    /// The UTF-8 sequence encodes a code point above U+10FFFF.
+    OutOfRange(u32),
+
+    /// The octets after the UTF-8 sequence are not zero.
+    NonZeroPadding {
+        /// Length of the UTF-8 sequence that precedes the padding.
+        len: u8,
+    },
+
+    /// The code point is a surrogate or above U+10FFFF and cannot be encoded.
+    InvalidCodePoint(u32),
+
+    /// Serialized input ends partway through a unit.
+    IncompleteUnit {
+        /// Number of octets of the trailing unit that are present (1–7).
+        len: u8,
+    },
+
+    /// Bytes viewed in place do not start on an 8-octet boundary.
+    Misaligned,
+
+    /// Serialized UTF-16 input ends partway through a 16-bit code unit.
+    IncompleteUtf16,
 }
 
-impl fmt::Display for Utf64Error {
+impl Utf64Error {
+    pub(crate) fn new(kind: ErrorKind, index: usize, unit: u64) -> Self {
+        Self { kind, index, unit }
+    }
+
+    /// Returns the reason the unit was rejected.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the index of the offending unit.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the raw value of the offending unit.
+    ///
+    /// For [`ErrorKind::InvalidCodePoint`] this is the rejected code point.
+    pub fn unit(&self) -> u64 {
+        self.unit
+    }
+
+    /// Returns the number of units that were verified as valid before the error.
+    ///
+    /// Like [`core::str::Utf8Error::valid_up_to`], this is the length of the longest
+    /// valid prefix of the input.
+    pub fn valid_up_to(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Utf64Error::InvalidUtf8 => write!(f, "invalid UTF-8 data"),
-            Utf64Error::InvalidUtf64 => write!(f, "invalid UTF64 encoding"),
-            Utf64Error::NonZeroReservedBits => {
-                write!(f, "reserved bits must be zero in UTF64 v1.0")
+            ErrorKind::NonZeroReserved => write!(f, "reserved bits must be zero in UTF64 v1.0"),
+            ErrorKind::ZeroLeadByte => write!(f, "first octet must be non-zero outside U+0000"),
+            ErrorKind::InvalidLeadByte(byte) => {
+                write!(f, "invalid UTF-8 lead byte {byte:#04X}")
+            }
+            ErrorKind::BadContinuation { octet, found } => write!(
+                f,
+                "expected UTF-8 continuation byte at octet {octet}, found {found:#04X}"
+            ),
+            ErrorKind::Overlong(code_point) => {
+                write!(f, "overlong UTF-8 encoding of U+{code_point:04X}")
+            }
+            ErrorKind::Surrogate(code_point) => {
+                write!(f, "surrogate code point U+{code_point:04X}")
+            }
+            ErrorKind::OutOfRange(code_point) => {
+                write!(f, "code point U+{code_point:04X} is above U+10FFFF")
+            }
+            ErrorKind::NonZeroPadding { len } => {
+                write!(f, "non-zero padding after {len}-octet UTF-8 sequence")
+            }
+            ErrorKind::InvalidCodePoint(code_point) => {
+                write!(f, "invalid code point {code_point:#X}")
+            }
+            ErrorKind::IncompleteUnit { len } => {
+                write!(f, "input ends with {len} of 8 octets of a unit")
+            }
+            ErrorKind::Misaligned => write!(f, "input is not aligned to 8 octets"),
+            ErrorKind::IncompleteUtf16 => {
+                write!(f, "input ends partway through a UTF-16 code unit")
             }
         }
     }
 }
 
-impl std::error::Error for Utf64Error {}
+impl fmt::Display for Utf64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::InvalidCodePoint(_)
+            | ErrorKind::IncompleteUnit { .. }
+            | ErrorKind::Misaligned
+            | ErrorKind::IncompleteUtf16 => {
+                write!(f, "{} at index {}", self.kind, self.index)
+            }
+            _ => write!(
+                f,
+                "invalid UTF64 unit {:#018X} at index {}: {}",
+                self.unit, self.index, self.kind
+            ),
+        }
+    }
+}
+
+impl core::error::Error for Utf64Error {}
+
+/// The error returned by [`String64::from_units`](crate::String64::from_units).
+///
+/// Like [`alloc::string::FromUtf8Error`], it gives back the buffer that failed
+/// validation together with the reason.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromUnitsError {
+    units: Vec<u64>,
+    error: Utf64Error,
+}
+
+#[cfg(feature = "alloc")]
+impl FromUnitsError {
+    pub(crate) fn new(units: Vec<u64>, error: Utf64Error) -> Self {
+        Self { units, error }
+    }
+
+    /// Returns the units that failed validation.
+    pub fn as_units(&self) -> &[u64] {
+        &self.units
+    }
+
+    /// Returns the units that failed validation, giving back ownership of the buffer.
+    pub fn into_units(self) -> Vec<u64> {
+        self.units
+    }
+
+    /// Returns the validation error describing the first invalid unit.
+    pub fn utf64_error(&self) -> Utf64Error {
+        self.error
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for FromUnitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for FromUnitsError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The error returned when an [`ArrayString64`](crate::ArrayString64) is too small for
+/// the characters being added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    capacity: usize,
+}
+
+impl CapacityError {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { capacity }
+    }
+
+    /// Returns the capacity of the string that ran out of room, in characters.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exceeds capacity of {} characters", self.capacity)
+    }
+}
+
+impl core::error::Error for CapacityError {}
 
 /// A specialized Result type for UTF64 operations.
-pub type Result<T> = std::result::Result<T, Utf64Error>;
+pub type Result<T> = core::result::Result<T, Utf64Error>;
@@ -0,0 +1,366 @@
+//! Streaming adapters between UTF-8 byte streams and serialized UTF64.
+//!
+//! [`Utf64Writer`] encodes UTF-8 written to it into UTF64 units, and [`Utf64Reader`]
+//! decodes a UTF64 byte stream back into UTF-8. Neither needs the whole input in
+//! memory, which covers the streaming requirement of SPECIFICATION §11.1 Level 3.
+
+use crate::byte_order::{BOM, ByteOrder};
+use crate::error::{ErrorKind, Utf64Error};
+use crate::unit::{encode_char, utf8_sequence_len, validate_unit};
+use std::io::{self, BufReader, Read, Write};
+
+/// Encodes UTF-8 bytes into serialized UTF64 units.
+///
+/// Multi-byte sequences may be split across calls to [`write`](Write::write); the
+/// incomplete tail is held back until the rest arrives. Invalid UTF-8 is reported as
+/// an [`io::ErrorKind::InvalidData`] error.
+///
+/// Call [`finish`](Self::finish) at the end of the input to detect a truncated final
+/// sequence.
+///
+/// If the inner writer fails, the held-back sequence is left as it was before the
+/// call, so the same bytes can be written again. Units the inner writer accepted
+/// before failing are not taken back, as with any [`Write::write_all`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use utf_64::{ByteOrder, String64, io::Utf64Writer};
+///
+/// let mut writer = Utf64Writer::new(Vec::new(), ByteOrder::BigEndian);
+/// writer.write_all(&[0x48, 0xE2, 0x82]).unwrap(); // 'H' and the start of '€'
+/// writer.write_all(&[0xAC]).unwrap();
+/// let bytes = writer.finish().unwrap();
+///
+/// assert_eq!(String64::from_bytes(&bytes, ByteOrder::BigEndian).unwrap(), "H€");
+/// ```
+pub struct Utf64Writer<W: Write> {
+    inner: W,
+    order: ByteOrder,
+    tail: Utf8Tail,
+    out: Vec<u8>,
+}
+
+impl<W: Write> Utf64Writer<W> {
+    /// Creates a writer that emits units to `inner` in the given byte order.
+    pub fn new(inner: W, order: ByteOrder) -> Self {
+        Self {
+            inner,
+            order,
+            tail: Utf8Tail::default(),
+            out: Vec::new(),
+        }
+    }
+
+    /// Writes a byte order mark to the underlying writer.
+    ///
+    /// Call this before writing any text so readers can detect the byte order.
+    pub fn write_bom(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.order.write_unit(BOM))
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Flushes the underlying writer and returns it.
+    ///
+    /// Fails if the input ended partway through a UTF-8 sequence.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.tail.is_pending() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete UTF-8 sequence at end of input",
+            ));
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Utf64Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Self {
+            inner,
+            order,
+            tail,
+            out,
+        } = self;
+
+        // Decode into a copy so a failed inner write leaves the tail untouched
+        out.clear();
+        let mut next = *tail;
+        let consumed = next.feed(buf, |ch| {
+            out.extend_from_slice(&order.write_unit(encode_char(ch)));
+        })?;
+
+        inner.write_all(out)?;
+        *tail = next;
+        Ok(consumed)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes UTF-8 arriving in arbitrary chunks, holding back a sequence split
+/// between chunks until the rest of it arrives.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Utf8Tail {
+    bytes: [u8; 4],
+    len: usize,
+}
+
+impl Utf8Tail {
+    /// Returns `true` if part of a sequence is being held back.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.len != 0
+    }
+
+    /// Decodes `buf`, calling `push` for every complete character.
+    ///
+    /// Returns the number of bytes consumed, following the [`Write::write`] contract:
+    /// invalid UTF-8 is reported as an error only if nothing before it was consumed,
+    /// otherwise it is reported by the next call.
+    pub(crate) fn feed(&mut self, buf: &[u8], mut push: impl FnMut(char)) -> io::Result<usize> {
+        let mut consumed = 0;
+        if self.is_pending() {
+            consumed = self.complete(buf, &mut push)?;
+        }
+
+        let rest = &buf[consumed..];
+        let (valid, error) = match std::str::from_utf8(rest) {
+            Ok(s) => (s, None),
+            Err(e) => {
+                let valid = std::str::from_utf8(&rest[..e.valid_up_to()]).expect("valid prefix");
+                (valid, Some(e))
+            }
+        };
+
+        valid.chars().for_each(&mut push);
+        consumed += valid.len();
+
+        if let Some(e) = error {
+            match e.error_len() {
+                // Truncated sequence at the end of the buffer: hold it back
+                None => {
+                    let tail = &rest[valid.len()..];
+                    self.bytes[..tail.len()].copy_from_slice(tail);
+                    self.len = tail.len();
+                    consumed += tail.len();
+                }
+                // Report the error now if nothing was accepted, otherwise on the next call
+                Some(_) if consumed == 0 => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(consumed)
+    }
+
+    /// Feeds bytes into the held-back incomplete sequence.
+    ///
+    /// Returns the number of bytes of `buf` consumed.
+    fn complete(&mut self, buf: &[u8], push: &mut impl FnMut(char)) -> io::Result<usize> {
+        let expected = utf8_sequence_len(self.bytes[0]);
+        let take = (expected - self.len).min(buf.len());
+        self.bytes[self.len..self.len + take].copy_from_slice(&buf[..take]);
+        self.len += take;
+
+        match std::str::from_utf8(&self.bytes[..self.len]) {
+            Ok(s) => {
+                self.len = 0;
+                push(s.chars().next().expect("non-empty sequence"));
+            }
+            // Still waiting for the rest of the sequence
+            Err(e) if e.error_len().is_none() => {}
+            Err(e) => {
+                self.len = 0;
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+        }
+        Ok(take)
+    }
+}
+
+/// Decodes a serialized UTF64 byte stream into UTF-8 bytes.
+///
+/// By default the byte order is detected from a leading byte order mark, which is
+/// stripped; without one, big-endian is assumed. Invalid units and a truncated final
+/// unit are reported as [`io::ErrorKind::InvalidData`] errors wrapping a
+/// [`Utf64Error`] whose index counts units after the BOM.
+///
+/// The source is buffered internally, so there is no need to wrap it in a
+/// [`BufReader`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+/// use utf_64::{ByteOrder, String64, io::Utf64Reader};
+///
+/// let bytes = String64::from("Hi🌍").to_bytes_with_bom(ByteOrder::LittleEndian);
+///
+/// let mut text = String::new();
+/// Utf64Reader::new(&bytes[..]).read_to_string(&mut text).unwrap();
+/// assert_eq!(text, "Hi🌍");
+/// ```
+pub struct Utf64Reader<R: Read> {
+    inner: BufReader<R>,
+    order: Option<ByteOrder>,
+    unit: [u8; 8],
+    unit_len: usize,
+    index: usize,
+    out: [u8; 4],
+    out_pos: usize,
+    out_len: usize,
+    error: Option<io::Error>,
+}
+
+impl<R: Read> Utf64Reader<R> {
+    /// Creates a reader that detects the byte order from a leading BOM.
+    pub fn new(inner: R) -> Self {
+        Self::with_order(inner, None)
+    }
+
+    /// Creates a reader for a stream in a known byte order.
+    ///
+    /// No byte order mark is expected; a leading BOM would be rejected as an invalid
+    /// unit.
+    pub fn with_byte_order(inner: R, order: ByteOrder) -> Self {
+        Self::with_order(inner, Some(order))
+    }
+
+    fn with_order(inner: R, order: Option<ByteOrder>) -> Self {
+        Self {
+            inner: BufReader::new(inner),
+            order,
+            unit: [0; 8],
+            unit_len: 0,
+            index: 0,
+            out: [0; 4],
+            out_pos: 0,
+            out_len: 0,
+            error: None,
+        }
+    }
+
+    /// Returns the byte order in use, or `None` if it has not been detected yet.
+    pub fn byte_order(&self) -> Option<ByteOrder> {
+        self.order
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.inner.get_ref()
+    }
+
+    /// Unwraps this reader, returning the underlying reader.
+    ///
+    /// Any data buffered internally but not yet decoded is lost.
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+
+    /// Reads the next serialized unit, or `None` at a clean end of stream.
+    fn read_unit(&mut self) -> io::Result<Option<[u8; 8]>> {
+        while self.unit_len < 8 {
+            match self.inner.read(&mut self.unit[self.unit_len..]) {
+                Ok(0) => break,
+                Ok(n) => self.unit_len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        match self.unit_len {
+            0 => Ok(None),
+            8 => {
+                self.unit_len = 0;
+                Ok(Some(self.unit))
+            }
+            len => Err(invalid_data(Utf64Error::new(
+                ErrorKind::IncompleteUnit { len: len as u8 },
+                self.index,
+                0,
+            ))),
+        }
+    }
+
+    /// Decodes the next character, or `None` at the end of the stream.
+    fn next_char(&mut self) -> io::Result<Option<char>> {
+        let Some(mut bytes) = self.read_unit()? else {
+            return Ok(None);
+        };
+
+        let order = match self.order {
+            Some(order) => order,
+            None => {
+                let detected = ByteOrder::from_bom(&bytes);
+                self.order = Some(detected.unwrap_or(ByteOrder::BigEndian));
+                if detected.is_some() {
+                    match self.read_unit()? {
+                        Some(next) => bytes = next,
+                        None => return Ok(None),
+                    }
+                }
+                self.order.expect("byte order just set")
+            }
+        };
+
+        let unit = order.read_unit(bytes);
+        let ch = validate_unit(unit)
+            .map_err(|kind| invalid_data(Utf64Error::new(kind, self.index, unit)))?;
+        self.index += 1;
+        Ok(Some(ch))
+    }
+}
+
+impl<R: Read> Read for Utf64Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        let mut written = 0;
+        while written < buf.len() {
+            if self.out_pos < self.out_len {
+                let n = (self.out_len - self.out_pos).min(buf.len() - written);
+                buf[written..written + n]
+                    .copy_from_slice(&self.out[self.out_pos..self.out_pos + n]);
+                self.out_pos += n;
+                written += n;
+                continue;
+            }
+
+            match self.next_char() {
+                Ok(Some(ch)) => {
+                    self.out_len = ch.encode_utf8(&mut self.out).len();
+                    self.out_pos = 0;
+                }
+                Ok(None) => break,
+                // Hand out what was decoded so far and report the error next time
+                Err(e) if written > 0 => {
+                    self.error = Some(e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(written)
+    }
+}
+
+fn invalid_data(error: Utf64Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
@@ -10,7 +10,7 @@
 //! ## Example
 //!
 //! ```
-//! use utf64::String64;
+//! use utf_64::String64;
 //!
 //! let text = String64::from("Hello, 世界!");
 //! assert_eq!(text.len(), 10);
@@ -18,16 +18,67 @@
 //! let decoded: String = text.to_string().unwrap();
 //! assert_eq!(decoded, "Hello, 世界!");
 //! ```
+//!
+//! ## Crate features
+//!
+//! - `std` (enabled by default): implements `std::io::Write` for `String64` and
+//!   provides the `io` module's adapters. Implies `alloc`.
+//! - `alloc` (enabled by `std`): `String64`, the conversions from `Str64` to owned
+//!   strings and bytes, and searching for `str` patterns. Without `std` the crate is
+//!   `no_std`; without `alloc` as well it needs no allocator, leaving
+//!   [`ArrayString64`] and the read-only [`Str64`] API.
+//! - `serde`: `Serialize` and `Deserialize` for `String64`. Implies `alloc`.
+//! - `mmap`: `mmap::MappedString64`, a read-only view of a memory-mapped UTF64 file.
+//!   Implies `std`.
+//! - `bytemuck`: `Pod`, `Zeroable` and `TransparentWrapper<u64>` for [`Char64`], for
+//!   casting slices of units to and from bytes without copying.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+pub mod array_string64;
+pub mod byte_order;
+#[cfg(feature = "bytemuck")]
+mod bytemuck_impl;
+pub mod char64;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod pattern;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod simd;
+pub mod str64;
+#[cfg(feature = "alloc")]
 pub mod string64;
+pub mod unit;
 
-pub use error::{Result, Utf64Error};
+pub use array_string64::ArrayString64;
+pub use byte_order::{BOM, ByteOrder};
+pub use char64::Char64;
+#[cfg(feature = "alloc")]
+pub use error::FromUnitsError;
+pub use error::{CapacityError, ErrorKind, Result, Utf64Error};
+#[cfg(feature = "mmap")]
+pub use mmap::MappedString64;
+pub use pattern::Pattern64;
+pub use str64::Str64;
+#[cfg(feature = "alloc")]
 pub use string64::String64;
+pub use unit::{decode_unit, encode_code_point, validate_units};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
+    use std::borrow::BorrowMut;
+    use std::prelude::rust_2024::*;
+    use std::{format, vec};
 
     #[test]
     fn test_ascii_roundtrip() {
@@ -93,6 +144,45 @@ mod tests {
         assert_eq!(format!("{:?}", utf64), "String64(\"test\")");
     }
 
+    #[test]
+    fn test_display_honours_formatting_options() {
+        let text = "héllo 🌍";
+        let s = String64::from(text);
+        for (actual, expected) in [
+            (format!("{s:12}"), format!("{text:12}")),
+            (format!("{s:>12}"), format!("{text:>12}")),
+            (format!("{s:*^13}"), format!("{text:*^13}")),
+            (format!("{s:.3}"), format!("{text:.3}")),
+            (format!("{s:-<6.2}"), format!("{text:-<6.2}")),
+            (format!("{s:3}"), format!("{text:3}")),
+        ] {
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_display_invalid_units_are_replaced() {
+        let mut s = String64::from("a?b");
+        let slice: &mut [u64] = s.borrow_mut();
+        slice[1] = 0x41000000_00000001;
+        assert_eq!(format!("{s}"), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn test_debug_escapes_like_str() {
+        let text = "tab\there \"quoted\" it's \u{301}\n";
+        let s = String64::from(text);
+        assert_eq!(format!("{s:?}"), format!("String64({text:?})"));
+    }
+
+    #[test]
+    fn test_debug_escapes_invalid_units_inline() {
+        let mut s = String64::from("a?b");
+        let slice: &mut [u64] = s.borrow_mut();
+        slice[1] = 0x41000000_00000001;
+        assert_eq!(format!("{s:?}"), "String64(\"a\\u{?0x4100000000000001}b\")");
+    }
+
     #[test]
     fn test_clone_and_equality() {
         let utf64_1 = String64::from("test");
@@ -138,6 +228,148 @@ mod tests {
         assert_eq!(upper_bits, 0xF09F9880);
     }
 
+    fn with_unit(unit: u64) -> String64 {
+        let mut s = String64::from("A");
+        let slice: &mut [u64] = s.borrow_mut();
+        slice[0] = unit;
+        s
+    }
+
+    #[test]
+    fn test_rejects_overlong_encoding() {
+        // '/' (U+002F) encoded in two bytes as C0 AF
+        let s = with_unit(0xC0AF0000_00000000);
+        assert_eq!(s.to_string().unwrap_err().kind(), ErrorKind::Overlong(0x2F));
+
+        // U+007F encoded in three bytes
+        let s = with_unit(0xE081BF00_00000000);
+        assert_eq!(s.to_string().unwrap_err().kind(), ErrorKind::Overlong(0x7F));
+    }
+
+    #[test]
+    fn test_rejects_bad_continuation() {
+        let s = with_unit(0xE2410000_00000000);
+        assert_eq!(
+            s.to_string().unwrap_err().kind(),
+            ErrorKind::BadContinuation {
+                octet: 1,
+                found: 0x41
+            }
+        );
+
+        // Stray continuation byte as the first octet
+        let s = with_unit(0x80000000_00000000);
+        assert_eq!(
+            s.to_string().unwrap_err().kind(),
+            ErrorKind::InvalidLeadByte(0x80)
+        );
+    }
+
+    #[test]
+    fn test_rejects_surrogate_and_out_of_range() {
+        // U+D800
+        let s = with_unit(0xEDA08000_00000000);
+        assert_eq!(
+            s.to_string().unwrap_err().kind(),
+            ErrorKind::Surrogate(0xD800)
+        );
+
+        // U+110000
+        let s = with_unit(0xF4908080_00000000);
+        assert_eq!(
+            s.to_string().unwrap_err().kind(),
+            ErrorKind::OutOfRange(0x110000)
+        );
+    }
+
+    #[test]
+    fn test_rejects_nonzero_padding() {
+        // 'A' followed by a stray octet in the upper half
+        let s = with_unit(0x41000001_00000000);
+        assert_eq!(
+            s.to_string().unwrap_err().kind(),
+            ErrorKind::NonZeroPadding { len: 1 }
+        );
+    }
+
+    #[test]
+    fn test_rejects_nonzero_reserved_bits() {
+        let s = with_unit(0x41000000_00000001);
+        assert_eq!(
+            s.to_string().unwrap_err().kind(),
+            ErrorKind::NonZeroReserved
+        );
+    }
+
+    #[test]
+    fn test_error_position() {
+        let mut s = String64::from("Hello");
+        let slice: &mut [u64] = s.borrow_mut();
+        slice[3] = 0xC0AF0000_00000000;
+
+        let err = s.to_string().unwrap_err();
+        assert_eq!(err.index(), 3);
+        assert_eq!(err.valid_up_to(), 3);
+        assert_eq!(err.unit(), 0xC0AF0000_00000000);
+        assert_eq!(
+            err.to_string(),
+            "invalid UTF64 unit 0xC0AF000000000000 at index 3: overlong UTF-8 encoding of U+002F"
+        );
+    }
+
+    #[test]
+    fn test_boundary_code_points_roundtrip() {
+        let original = "\u{7F}\u{80}\u{7FF}\u{800}\u{FFFF}\u{10000}\u{10FFFF}";
+        let utf64 = String64::from(original);
+        assert_eq!(utf64.to_string().unwrap(), original);
+    }
+
+    #[test]
+    fn test_nul_roundtrip() {
+        let s = String64::from("a\0b");
+        assert_eq!(s.as_slice()[1], 0);
+        assert_eq!(s.to_string().unwrap(), "a\0b");
+
+        // Long enough for the SIMD kernels to see whole blocks of U+0000
+        let original = "\0".repeat(40) + "x\0ü\0";
+        let s = String64::from(original.as_str());
+        assert!(validate_units(s.as_slice()).is_ok());
+        assert_eq!(s.to_string().unwrap(), original);
+        let s = String64::from_units(s.into_units()).unwrap();
+        assert_eq!(s, original.as_str());
+
+        // Only the all-zero unit may have a zero first octet
+        let s = with_unit(0x00410000_00000000);
+        assert_eq!(s.to_string().unwrap_err().kind(), ErrorKind::ZeroLeadByte);
+    }
+
+    #[test]
+    fn test_encode_code_point() {
+        assert_eq!(encode_code_point(0x41), Ok(0x41000000_00000000));
+        assert_eq!(encode_code_point(0x10FFFF), Ok(0xF48FBFBF_00000000));
+
+        for code_point in [0xD800, 0xDFFF, 0x110000, u32::MAX] {
+            let err = encode_code_point(code_point).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidCodePoint(code_point));
+        }
+    }
+
+    #[test]
+    fn test_decode_unit_inverts_encode() {
+        for code_point in 0..=0x10FFFF {
+            match encode_code_point(code_point) {
+                Ok(unit) => assert_eq!(decode_unit(unit), Ok(char::from_u32(code_point).unwrap())),
+                Err(_) => assert!((0xD800..=0xDFFF).contains(&code_point)),
+            }
+        }
+        assert_eq!(encode_code_point(0), Ok(0));
+        assert_eq!(decode_unit(0), Ok('\0'));
+        assert_eq!(
+            decode_unit(0xEDA08000_00000000).unwrap_err().kind(),
+            ErrorKind::Surrogate(0xD800)
+        );
+    }
+
     #[test]
     fn test_hash() {
         use std::collections::HashMap;
@@ -158,12 +390,47 @@ mod tests {
         assert_eq!(s1.cmp(&s3), std::cmp::Ordering::Equal);
     }
 
+    #[test]
+    fn test_ord_matches_str_order() {
+        let words = [
+            "", "a", "ab", "b", "z", "é", "€", "😀", "a😀", "aé", "\u{7F}", "\u{80}",
+        ];
+        for a in words {
+            for b in words {
+                let (a64, b64) = (String64::from(a), String64::from(b));
+                assert_eq!(a64.cmp(&b64), a.cmp(b), "{a:?} vs {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ord_is_total_for_invalid_data() {
+        let bad1 = with_unit(0x41000000_00000001);
+        let bad2 = with_unit(0x41000000_00000002);
+        assert_ne!(bad1.cmp(&bad2), std::cmp::Ordering::Equal);
+        assert_eq!(bad1.cmp(&bad1.clone()), std::cmp::Ordering::Equal);
+
+        let mut strings = vec![bad2.clone(), String64::from("B"), bad1.clone()];
+        strings.sort();
+        assert_eq!(strings, vec![bad1, bad2, String64::from("B")]);
+    }
+
+    #[test]
+    fn test_partial_eq_str_edge_cases() {
+        assert_eq!(String64::from("héllo"), "héllo");
+        assert_ne!(String64::from("héllo"), "héll");
+        assert_ne!(String64::from("héll"), "héllo");
+        assert_ne!(with_unit(0x41000000_00000001), "A");
+        assert_eq!(String64::new(), "");
+    }
+
     #[test]
     fn test_indexing() {
         let s = String64::from("Hi");
         let first = s[0];
-        let upper_bits = (first >> 32) as u32;
-        assert_eq!(upper_bits, 0x48000000); // 'H'
+        assert_eq!(first, 'H');
+        assert_eq!(first.code_point(), 0x48);
+        assert_eq!(u64::from(first), 0x48000000_00000000);
     }
 
     #[test]
@@ -171,6 +438,97 @@ mod tests {
         let s = String64::from("Hello");
         let slice = &s[1..3];
         assert_eq!(slice.len(), 2);
+        assert_eq!(slice[0], 'e');
+        assert_eq!(slice, "el");
+        assert_eq!(&slice[1..], "l");
+    }
+
+    #[test]
+    fn test_str64_from_units() {
+        let units = [0x48000000_00000000, 0x69000000_00000000];
+        let s = Str64::from_units(&units).unwrap();
+        assert_eq!(s.to_string().unwrap(), "Hi");
+        assert_eq!(format!("{s} {s:?}"), "Hi Str64(\"Hi\")");
+
+        let err = Str64::from_units(&[0x48000000_00000000, 0x1]).unwrap_err();
+        assert_eq!(err.index(), 1);
+    }
+
+    #[test]
+    fn test_str64_smart_pointers() {
+        use std::borrow::Cow;
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        let owned = String64::from("Hello");
+        let sub: &Str64 = &owned[1..4];
+
+        let cow: Cow<Str64> = Cow::Borrowed(sub);
+        assert_eq!(cow.into_owned(), "ell");
+
+        let boxed: Box<Str64> = Box::from(sub);
+        assert_eq!(&*boxed.clone(), "ell");
+        assert_eq!(String64::from(boxed), "ell");
+
+        let rc: Rc<Str64> = Rc::from(sub);
+        let arc: Arc<Str64> = Arc::from(owned.clone());
+        assert_eq!(&*rc, "ell");
+        assert_eq!(&*arc, "Hello");
+    }
+
+    #[test]
+    fn test_str64_hash_matches_string64() {
+        use std::collections::HashMap;
+        let mut map: HashMap<String64, i32> = HashMap::new();
+        map.insert(String64::from("key"), 1);
+        let key: &Str64 = &String64::from("key");
+        assert_eq!(map.get(key), Some(&1));
+    }
+
+    #[test]
+    fn test_char64_conversions() {
+        let ch = Char64::from('😀');
+        assert_eq!(ch.code_point(), 0x1F600);
+        assert_eq!(ch.utf8_len(), 4);
+        assert_eq!(ch.utf8_bytes(), [0xF0, 0x9F, 0x98, 0x80]);
+        assert_eq!(ch.reserved_bits(), 0);
+        assert_eq!(char::from(ch), '😀');
+        assert_eq!(Char64::try_from(0xF09F9880_00000000), Ok(ch));
+
+        let err = Char64::try_from(0xF09F9880_00000001).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NonZeroReserved);
+    }
+
+    #[test]
+    fn test_char64_nul() {
+        let nul = Char64::from('\0');
+        assert_eq!(u64::from(nul), 0);
+        assert_eq!(Char64::try_from(0), Ok(nul));
+        assert_eq!(nul.utf8_len(), 1);
+        assert_eq!(char::from(nul), '\0');
+        assert_eq!(format!("{nul:?}"), "Char64('\\0')");
+    }
+
+    #[test]
+    fn test_char64_ord_by_code_point() {
+        let mut chars: Vec<Char64> = "😀zA€é".chars().map(Char64::from).collect();
+        chars.sort();
+        let sorted: Vec<char> = chars.into_iter().map(char::from).collect();
+        assert_eq!(sorted, vec!['A', 'z', 'é', '€', '😀']);
+    }
+
+    #[test]
+    fn test_char64_from_corrupted_string() {
+        let s = with_unit(0x41000000_00000001);
+        assert_eq!(s[0].to_char(), '\u{FFFD}');
+        assert_eq!(s[0].reserved_bits(), 1);
+        assert_eq!(s[0].utf8_bytes(), [0x41, 0, 0, 0]);
+        assert_eq!(s[0].code_point(), 0xFFFD);
+        assert_eq!(s[0].utf8_len(), 3);
+        assert_eq!(
+            format!("{:?}", s[0]),
+            "Char64(<invalid 0x4100000000000001>)"
+        );
     }
 
     #[test]
@@ -189,6 +547,57 @@ mod tests {
         assert_eq!(s.len(), 2);
     }
 
+    #[test]
+    fn test_iterators_do_not_panic_on_invalid_units() {
+        let s = with_unit(0x41000000_00000001) + "b";
+        let chars: Vec<char> = (&s).into_iter().collect();
+        assert_eq!(chars, vec!['\u{FFFD}', 'b']);
+
+        let chars: Vec<char> = s.into_iter().collect();
+        assert_eq!(chars, vec!['\u{FFFD}', 'b']);
+    }
+
+    #[test]
+    fn test_try_chars() {
+        let mut s = String64::from("a🌍c");
+        let slice: &mut [u64] = s.borrow_mut();
+        slice[1] = 0xC0AF0000_00000000;
+
+        let mut iter = s.try_chars();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some(Ok('a')));
+
+        let err = iter.next().unwrap().unwrap_err();
+        assert_eq!(err.index(), 1);
+        assert_eq!(err.kind(), ErrorKind::Overlong(0x2F));
+
+        assert_eq!(iter.next(), Some(Ok('c')));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_to_string_lossy() {
+        let mut s = String64::from("a€c😀");
+        let slice: &mut [u64] = s.borrow_mut();
+        slice[1] = 0xE2820000_00000000; // truncated '€'
+        slice[3] |= 0x1; // reserved bit set
+
+        assert_eq!(s.to_string_lossy(), "a\u{FFFD}c\u{FFFD}");
+        assert_eq!(String64::from("ok").to_string_lossy(), "ok");
+    }
+
+    #[test]
+    fn test_from_units_lossy() {
+        let units = vec![
+            0x41000000_00000000,
+            0x41000001_00000000,
+            0xF4908080_00000000,
+        ];
+        let s = String64::from_units_lossy(units);
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.to_string().unwrap(), "A\u{FFFD}\u{FFFD}");
+    }
+
     #[test]
     fn test_from_iterator() {
         let chars = vec!['H', 'e', 'l', 'l', 'o'];
@@ -217,6 +626,139 @@ mod tests {
         assert_eq!(s.to_string().unwrap(), "Hello World");
     }
 
+    #[test]
+    fn test_push_and_pop() {
+        let mut s = String64::new();
+        s.push('a');
+        s.push_str("é🌍");
+        assert_eq!(s, "aé🌍");
+        assert_eq!(s.pop(), Some('🌍'));
+        assert_eq!(s.pop(), Some('é'));
+        assert_eq!(s.pop(), Some('a'));
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut s = String64::from("ac");
+        s.insert(1, 'b');
+        s.insert(3, '€');
+        assert_eq!(s, "abc€");
+        s.insert_str(0, ">> ");
+        assert_eq!(s, ">> abc€");
+        assert_eq!(s.remove(3), 'a');
+        assert_eq!(s, ">> bc€");
+    }
+
+    #[test]
+    fn test_truncate_clear_split_off() {
+        let mut s = String64::from("Hello, 世界");
+        let tail = s.split_off(7);
+        assert_eq!(s, "Hello, ");
+        assert_eq!(tail, "世界");
+
+        s.truncate(5);
+        assert_eq!(s, "Hello");
+        s.truncate(50);
+        assert_eq!(s, "Hello");
+
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut s = String64::from("a1b2c3€");
+        s.retain(|ch| !ch.is_ascii_digit());
+        assert_eq!(s, "abc€");
+    }
+
+    #[test]
+    fn test_drain_and_replace_range() {
+        let mut s = String64::from("Hello, World");
+        let drained: String = s.drain(5..7).collect();
+        assert_eq!(drained, ", ");
+        assert_eq!(s, "HelloWorld");
+
+        let mut drain = s.drain(5..);
+        assert_eq!(drain.len(), 5);
+        assert_eq!(drain.next_back(), Some('d'));
+        drop(drain);
+        assert_eq!(s, "Hello");
+
+        s.replace_range(1..4, "ipp🌍");
+        assert_eq!(s, "Hipp🌍o");
+        s.replace_range(.., "");
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_fmt_write() {
+        use std::fmt::Write;
+
+        let mut s = String64::from("x = ");
+        write!(s, "{} {:>3}", 42, '€').unwrap();
+        s.write_char('!').unwrap();
+        assert_eq!(s, "x = 42   €!");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_write_buffers_split_sequences() {
+        use std::io::Write;
+
+        let bytes = "a😀b".as_bytes();
+        let mut s = String64::new();
+        for byte in bytes {
+            s.write_all(std::slice::from_ref(byte)).unwrap();
+        }
+        s.flush().unwrap();
+        assert_eq!(s, "a😀b");
+
+        s.write_all(&bytes[1..3]).unwrap();
+        assert!(s.flush().is_err());
+        assert_eq!(s, "a😀b"); // held-back bytes are not part of the string
+        s.write_all(&bytes[3..5]).unwrap();
+        assert_eq!(s, "a😀b😀");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_write_rejects_invalid_utf8() {
+        use std::io::Write;
+
+        let mut s = String64::new();
+        let err = s.write_all(b"ok\xC0\xAF").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(s, "ok");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_write_pending_bytes_dropped_by_other_mutators() {
+        use std::io::Write;
+
+        let mut s = String64::new();
+        s.write_all(&[0xE2]).unwrap();
+        s.push('x');
+        s.flush().unwrap();
+        let err = s.write_all(&[0x82, 0xAC]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(s, "x");
+
+        s.write_all(&[0xE2]).unwrap();
+        s.clear();
+        s.flush().unwrap();
+        assert!(s.write_all(&[0x82, 0xAC]).is_err());
+        assert!(s.is_empty());
+
+        // The held-back bytes live behind a single nullable pointer.
+        assert_eq!(
+            std::mem::size_of::<String64>(),
+            std::mem::size_of::<Vec<u64>>() + std::mem::size_of::<usize>()
+        );
+    }
+
     #[test]
     fn test_partial_eq_str() {
         let s = String64::from("test");
@@ -245,18 +787,1054 @@ mod tests {
         assert_eq!(result.unwrap(), "test");
     }
 
+    #[test]
+    fn test_from_units_roundtrip() {
+        let original = String64::from("Hi🌍");
+        let units = original.clone().into_units();
+        assert_eq!(String64::from_units(units).unwrap(), original);
+    }
+
+    #[test]
+    fn test_from_units_returns_buffer() {
+        let units = vec![0x48000000_00000000, 0xEDA08000_00000000];
+        let err = String64::from_units(units.clone()).unwrap_err();
+        assert_eq!(err.utf64_error().index(), 1);
+        assert_eq!(err.utf64_error().kind(), ErrorKind::Surrogate(0xD800));
+        assert_eq!(err.into_units(), units);
+
+        // The unchecked constructors only skip validation; invalid units still surface
+        let s = String64::from_units_unchecked(units.clone());
+        assert_eq!(s.to_string().unwrap_err().index(), 1);
+        assert_eq!(
+            Str64::from_units_unchecked(&units).to_string_lossy(),
+            "H\u{FFFD}"
+        );
+    }
+
+    #[test]
+    fn test_try_from_unit_slice() {
+        let units: &[u64] = &[0x48000000_00000000, 0x69000000_00000000];
+        assert_eq!(String64::try_from(units).unwrap(), "Hi");
+
+        let units: &[u64] = &[0x48000000_00000000, 0x69000000_00000001];
+        let err = String64::try_from(units).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NonZeroReserved);
+    }
+
+    #[test]
+    fn test_byte_order_layouts() {
+        // SPECIFICATION Appendix A.4
+        let s = String64::from("Hi🌍");
+        let be = s.to_be_bytes();
+        assert_eq!(&be[16..], &[0xF0, 0x9F, 0x8C, 0x8D, 0, 0, 0, 0]);
+
+        let le = s.to_le_bytes();
+        assert_eq!(&le[16..], &[0, 0, 0, 0, 0x8D, 0x8C, 0x9F, 0xF0]);
+
+        assert_eq!(String64::from_bytes(&be, ByteOrder::BigEndian).unwrap(), s);
+        assert_eq!(
+            String64::from_bytes(&le, ByteOrder::LittleEndian).unwrap(),
+            s
+        );
+        assert_eq!(
+            String64::from_bytes(&s.to_ne_bytes(), ByteOrder::NATIVE).unwrap(),
+            s
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_detect() {
+        let s = String64::from("BOM");
+        for order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+            let bytes = s.to_bytes_with_bom(order);
+            assert_eq!(ByteOrder::from_bom(&bytes), Some(order));
+            assert_eq!(String64::from_bytes_detect(&bytes).unwrap(), s);
+        }
+
+        // A leading U+FEFF character unit is accepted as a BOM too
+        let mut bytes = String64::from("\u{FEFF}").to_le_bytes();
+        bytes.extend(s.to_le_bytes());
+        assert_eq!(String64::from_bytes_detect(&bytes).unwrap(), s);
+
+        // Without a BOM, big-endian is assumed
+        assert_eq!(String64::from_bytes_detect(&s.to_be_bytes()).unwrap(), s);
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_incomplete_unit() {
+        let mut bytes = String64::from("Hi").to_bytes_with_bom(ByteOrder::BigEndian);
+        bytes.pop();
+
+        let err = String64::from_bytes_detect(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IncompleteUnit { len: 7 });
+        assert_eq!(err.index(), 1);
+    }
+
+    #[test]
+    fn test_from_bytes_validates_units() {
+        let bytes = String64::from("Hi").to_le_bytes();
+        let err = String64::from_bytes(&bytes, ByteOrder::BigEndian).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NonZeroReserved);
+        assert_eq!(err.index(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_writer_handles_split_sequences() {
+        use std::io::Write;
+
+        let text = "aé€😀z";
+        for split in 0..=text.len() {
+            let mut writer = io::Utf64Writer::new(Vec::new(), ByteOrder::LittleEndian);
+            writer.write_all(&text.as_bytes()[..split]).unwrap();
+            writer.write_all(&text.as_bytes()[split..]).unwrap();
+            let bytes = writer.finish().unwrap();
+
+            let decoded = String64::from_bytes(&bytes, ByteOrder::LittleEndian).unwrap();
+            assert_eq!(decoded, text);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_writer_rejects_invalid_utf8() {
+        use std::io::Write;
+
+        let mut writer = io::Utf64Writer::new(Vec::new(), ByteOrder::BigEndian);
+        let err = writer.write_all(b"ok\xFFno").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(writer.get_ref().len(), 16);
+
+        let mut writer = io::Utf64Writer::new(Vec::new(), ByteOrder::BigEndian);
+        writer.write_all(b"\xE2\x82").unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_writer_keeps_tail_when_inner_write_fails() {
+        use std::io::Write;
+
+        /// Fails the first write, then accepts everything.
+        struct FailOnce(bool, Vec<u8>);
+
+        impl Write for FailOnce {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if std::mem::replace(&mut self.0, false) {
+                    return Err(std::io::Error::other("transient"));
+                }
+                self.1.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = io::Utf64Writer::new(FailOnce(false, Vec::new()), ByteOrder::BigEndian);
+        writer.write_all(b"\xE2\x82").unwrap();
+        writer.get_mut().0 = true;
+        assert!(writer.write(b"\xACx").is_err());
+        writer.write_all(b"\xACx").unwrap();
+
+        let bytes = writer.finish().unwrap().1;
+        assert_eq!(
+            String64::from_bytes(&bytes, ByteOrder::BigEndian).unwrap(),
+            "€x"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_reader_roundtrip_with_small_reads() {
+        use std::io::Read;
+
+        let text = "Hello, 世界! 🌍";
+        let mut writer = io::Utf64Writer::new(Vec::new(), ByteOrder::LittleEndian);
+        writer.write_bom().unwrap();
+        std::io::Write::write_all(&mut writer, text.as_bytes()).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = io::Utf64Reader::new(&bytes[..]);
+        let mut decoded = Vec::new();
+        let mut buf = [0u8; 3];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            decoded.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(reader.byte_order(), Some(ByteOrder::LittleEndian));
+        assert_eq!(String::from_utf8(decoded).unwrap(), text);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_stream_roundtrip_with_nul() {
+        use std::io::{Read, Write};
+
+        let text = "\0leading, in\0side and trailing\0";
+        for bom in [false, true] {
+            let mut writer = io::Utf64Writer::new(Vec::new(), ByteOrder::BigEndian);
+            if bom {
+                writer.write_bom().unwrap();
+            }
+            for byte in text.as_bytes() {
+                writer.write_all(std::slice::from_ref(byte)).unwrap();
+            }
+            let bytes = writer.finish().unwrap();
+
+            let mut decoded = String::new();
+            io::Utf64Reader::new(&bytes[..])
+                .read_to_string(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, text);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_reader_reports_invalid_units() {
+        use std::io::Read;
+
+        let mut bytes = String64::from("ab").to_be_bytes();
+        bytes[15] = 1; // reserved bit of 'b'
+        let mut text = String::new();
+        let err = io::Utf64Reader::new(&bytes[..])
+            .read_to_string(&mut text)
+            .unwrap_err();
+        let inner = err.get_ref().unwrap().downcast_ref::<Utf64Error>().unwrap();
+        assert_eq!(inner.kind(), ErrorKind::NonZeroReserved);
+        assert_eq!(inner.index(), 1);
+
+        let bytes = &String64::from("ab").to_be_bytes()[..12];
+        let mut reader = io::Utf64Reader::with_byte_order(bytes, ByteOrder::BigEndian);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        let inner = err.get_ref().unwrap().downcast_ref::<Utf64Error>().unwrap();
+        assert_eq!(inner.kind(), ErrorKind::IncompleteUnit { len: 4 });
+    }
+
+    #[test]
+    fn test_find() {
+        let s = String64::from("abcabc 世界abc");
+        assert_eq!(s.find('c'), Some(2));
+        assert_eq!(s.rfind('c'), Some(11));
+        assert_eq!(s.find("abc"), Some(0));
+        assert_eq!(s.rfind("abc"), Some(9));
+        assert_eq!(s.find("世界"), Some(7));
+        assert_eq!(s.find(&String64::from("ca")), Some(2));
+        assert_eq!(s.find(&['界', ' '][..]), Some(6));
+        assert_eq!(s.rfind(|c: char| !c.is_ascii()), Some(8));
+        assert_eq!(s.find("abd"), None);
+        assert_eq!(s.find(""), Some(0));
+        assert_eq!(s.rfind(""), Some(s.len()));
+        assert_eq!(s[3..].find("abc"), Some(0));
+
+        // Needles longer than the haystack, and needles with repeated units
+        assert_eq!(String64::from("ab").find("abc"), None);
+        assert_eq!(String64::from("aaaab").find("aab"), Some(2));
+        assert_eq!(String64::from("baaaa").rfind("baa"), Some(0));
+
+        // Every needle up to length 4 over a small alphabet, against `str`
+        let haystack = "abaabbbaabaaaababbaabab";
+        let s = String64::from(haystack);
+        let mut needles = vec![String::new()];
+        for _ in 0..4 {
+            let longer: Vec<_> = needles
+                .iter()
+                .flat_map(|n| ["a", "b", "c"].map(|c| format!("{n}{c}")))
+                .collect();
+            for needle in &longer {
+                assert_eq!(s.find(needle.as_str()), haystack.find(needle.as_str()));
+                assert_eq!(s.rfind(needle.as_str()), haystack.rfind(needle.as_str()));
+            }
+            needles = longer;
+        }
+    }
+
+    #[test]
+    fn test_find_is_linear() {
+        // Boyer–Moore–Horspool rescans almost the whole needle at every position here
+        let haystack = String64::from("a".repeat(1_000_000).as_str());
+        let needle = "a".repeat(2000) + "b";
+        let started = std::time::Instant::now();
+        assert_eq!(haystack.find(needle.as_str()), None);
+        assert_eq!(haystack.rfind(needle.as_str()), None);
+        let needle = "b".to_string() + &"a".repeat(2000);
+        assert_eq!(haystack.find(needle.as_str()), None);
+        assert_eq!(haystack.rfind(needle.as_str()), None);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_prefix_suffix() {
+        let s = String64::from("prefix-body-suffix");
+        assert!(s.starts_with("prefix"));
+        assert!(s.starts_with('p'));
+        assert!(!s.starts_with("body"));
+        assert!(s.ends_with("suffix"));
+        assert!(s.ends_with(char::is_alphabetic));
+        assert!(s.contains("body"));
+        assert_eq!(s.strip_prefix("prefix-").unwrap(), "body-suffix");
+        assert_eq!(s.strip_suffix("-suffix").unwrap(), "prefix-body");
+        assert_eq!(s.strip_suffix('x').unwrap(), "prefix-body-suffi");
+        assert!(s.strip_prefix("body").is_none());
+        assert!(String64::new().strip_prefix('a').is_none());
+    }
+
+    #[test]
+    fn test_matches() {
+        let s = String64::from("aaaa");
+        let found: Vec<_> = s.match_indices("aa").map(|(i, m)| (i, m.len())).collect();
+        assert_eq!(found, [(0, 2), (2, 2)]);
+        assert_eq!(s.matches('a').count(), 4);
+
+        let empty: Vec<_> = String64::from("ab")
+            .match_indices("")
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(empty, [0, 1, 2]);
+    }
+
+    fn collect<'a>(pieces: impl Iterator<Item = &'a Str64>) -> Vec<String> {
+        pieces.map(|p| p.to_string().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_split() {
+        let s = String64::from("a,b,,c,");
+        assert_eq!(collect(s.split(',')), ["a", "b", "", "c", ""]);
+        assert_eq!(collect(s.rsplit(',')), ["", "c", "", "b", "a"]);
+        assert_eq!(collect(s.split_terminator(',')), ["a", "b", "", "c"]);
+        assert_eq!(collect(s.splitn(2, ',')), ["a", "b,,c,"]);
+        assert_eq!(collect(s.splitn(0, ',')), Vec::<String>::new());
+        assert_eq!(collect(s.split(",,")), ["a,b", "c,"]);
+
+        let s = String64::from("ab");
+        assert_eq!(collect(s.split("")), ["", "a", "b", ""]);
+        assert_eq!(collect(s.rsplit("")), ["", "b", "a", ""]);
+
+        assert_eq!(collect(String64::new().split(',')), [""]);
+        assert_eq!(collect(String64::new().split_terminator(',')).len(), 0);
+    }
+
+    #[test]
+    fn test_split_whitespace_and_once() {
+        let s = String64::from("  hello \t wörld\n");
+        assert_eq!(collect(s.split_whitespace()), ["hello", "wörld"]);
+        assert_eq!(String64::from(" \t ").split_whitespace().count(), 0);
+
+        let (k, v) = s.split_once("lo").unwrap();
+        assert_eq!(k, "  hel");
+        assert_eq!(v, " \t wörld\n");
+        assert!(s.split_once('x').is_none());
+    }
+
+    #[test]
+    fn test_lines() {
+        let s = String64::from("one\r\ntwo\n\r\nthree\r");
+        assert_eq!(collect(s.lines()), ["one", "two", "", "three\r"]);
+        assert_eq!(collect(String64::from("\n").lines()), [""]);
+        assert_eq!(String64::new().lines().count(), 0);
+    }
+
+    #[test]
+    fn test_case_mapping() {
+        let s = String64::from("Straße ǅ İ");
+        assert_eq!(s.to_uppercase(), "STRASSE Ǆ İ");
+        assert_eq!(s.to_lowercase(), "straße ǆ i\u{307}");
+        assert_eq!(s.to_lowercase().len(), s.len() + 1);
+        assert_eq!(s.to_ascii_uppercase(), "STRAßE ǅ İ");
+        assert_eq!(s.to_ascii_lowercase(), "straße ǅ İ");
+        assert!(s.eq_ignore_ascii_case(&String64::from("sTRAßE ǅ İ")));
+        assert!(!s.eq_ignore_ascii_case(&String64::from("STRASSE ǅ İ")));
+        assert!(!s.eq_ignore_ascii_case(&s[1..]));
+
+        // Final_Sigma, checked against `str::to_lowercase`
+        assert_eq!(String64::from("ΟΔΟΣ").to_lowercase(), "οδος");
+        for text in [
+            "ΟΔΟΣ.",
+            "Σ",
+            "ΑΣ\u{301}Α",
+            "ΑΣ\u{301} Α",
+            "ΑΣ' ΣΑ",
+            "ΟΔΟΣ\tΣ1",
+        ] {
+            assert_eq!(
+                String64::from(text).to_lowercase(),
+                text.to_lowercase().as_str(),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_make_ascii_in_place() {
+        let mut s = String64::from("MiXeD ünits");
+        let units: &mut [u64] = s.borrow_mut();
+        units[0] |= 1; // invalid units are left alone
+        let ptr = s.as_slice().as_ptr();
+
+        s.make_ascii_lowercase();
+        assert_eq!(&s[1..], "ixed ünits");
+        s.make_ascii_uppercase();
+        assert_eq!(&s[1..], "IXED üNITS");
+        assert_eq!(s.as_slice()[0], String64::from("M").as_slice()[0] | 1);
+        assert_eq!(s.as_slice().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_trim() {
+        let s = String64::from(" \t héllo \n");
+        assert_eq!(s.trim(), "héllo");
+        assert_eq!(s.trim_start(), "héllo \n");
+        assert_eq!(s.trim_end(), " \t héllo");
+        assert_eq!(String64::from("   ").trim(), "");
+        assert_eq!(String64::from("abab-ab-abab").trim_matches("ab"), "-ab-");
+        assert_eq!(String64::from("abc").trim_matches(""), "abc");
+        assert_eq!(
+            String64::from("123x45").trim_start_matches(char::is_numeric),
+            "x45"
+        );
+        assert_eq!(
+            String64::from("123x45").trim_end_matches(char::is_numeric),
+            "123x"
+        );
+    }
+
+    #[test]
+    fn test_reverse_iteration() {
+        let s = String64::from("abc€d");
+        let rev: String = s.chars_lossy().rev().collect();
+        assert_eq!(rev, "d€cba");
+        let rev: String = s.clone().into_iter().rev().collect();
+        assert_eq!(rev, "d€cba");
+
+        let mut iter = s.chars_lossy();
+        assert_eq!(iter.nth(1), Some('b'));
+        assert_eq!(iter.nth_back(1), Some('€'));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some('c'));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let mut iter = s.into_iter();
+        assert_eq!(iter.nth(10), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_char_indices_and_units() {
+        let mut s = String64::from("a€b");
+        let units: &mut [u64] = s.borrow_mut();
+        units[2] |= 1;
+
+        let indices: Vec<_> = s.char_indices().collect();
+        assert_eq!(indices, [(0, 'a'), (1, '€'), (2, '\u{FFFD}')]);
+        assert_eq!(s.char_indices().nth_back(1), Some((1, '€')));
+
+        let units: Vec<Char64> = s.units().collect();
+        assert_eq!(units[1], '€');
+        assert_eq!(units[2].reserved_bits(), 1);
+        assert_eq!(s.units().next_back(), Some(units[2]));
+
+        let errors: Vec<_> = s.try_chars().rev().map(|r| r.is_err()).collect();
+        assert_eq!(errors, [true, false, false]);
+        assert_eq!(s.try_chars().nth(1), Some(Ok('€')));
+        assert_eq!(s.try_chars().nth_back(0).unwrap().unwrap_err().index(), 2);
+        assert_eq!(s.try_chars().nth_back(3), None);
+    }
+
+    fn check_widen_kernel(widen: simd::WidenAscii) {
+        use std::mem::MaybeUninit;
+
+        let ascii: Vec<u8> = (0..100u8).map(|i| b' ' + i % 90).collect();
+        for len in 0..ascii.len() {
+            let mut out = vec![MaybeUninit::uninit(); len];
+            // SAFETY: callers only pass kernels this CPU supports.
+            let n = unsafe { widen(&ascii[..len], &mut out) };
+            assert!(n <= len);
+            for (slot, &byte) in out[..n].iter().zip(&ascii) {
+                // SAFETY: the kernel initialized the first `n` slots.
+                assert_eq!(unsafe { slot.assume_init() }, (byte as u64) << 56);
+            }
+        }
+
+        // Stops before the block holding the first non-ASCII byte
+        let mut mixed = ascii.clone();
+        mixed[70] = 0xC3;
+        let mut out = vec![MaybeUninit::uninit(); mixed.len()];
+        // SAFETY: as above.
+        assert!(unsafe { widen(&mixed, &mut out) } <= 70);
+    }
+
+    fn check_count_kernel(count: simd::CountChars) {
+        let text = "ASCII, then ünïcödé, 日本語 and 🦀🦀 crabs, and ASCII again for a while";
+        let bytes = text.as_bytes();
+        for start in 0..bytes.len() {
+            for end in start..=bytes.len() {
+                let slice = &bytes[start..end];
+                // SAFETY: callers only pass kernels this CPU supports.
+                let (read, n) = unsafe { count(slice) };
+                assert!(read <= slice.len());
+                let expected = slice[..read].iter().filter(|&&b| b & 0xC0 != 0x80).count();
+                assert_eq!(n, expected, "{start}..{end}");
+            }
+        }
+        for sample in [
+            text,
+            "",
+            "€",
+            "🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀",
+        ] {
+            assert_eq!(simd::count_chars(sample.as_bytes()), sample.chars().count());
+        }
+    }
+
+    #[test]
+    fn test_count_chars_kernels() {
+        check_count_kernel(simd::count_chars_swar);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if std::arch::is_x86_feature_detected!("sse2") {
+                check_count_kernel(simd::x86_64::count_chars_sse2);
+            }
+            if std::arch::is_x86_feature_detected!("avx2") {
+                check_count_kernel(simd::x86_64::count_chars_avx2);
+            }
+        }
+
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                check_count_kernel(simd::aarch64::count_chars_neon);
+            }
+        }
+    }
+
+    #[test]
+    fn test_widen_ascii_kernels() {
+        check_widen_kernel(simd::widen_ascii_swar);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if std::arch::is_x86_feature_detected!("sse2") {
+                check_widen_kernel(simd::x86_64::widen_ascii_sse2);
+            }
+            if std::arch::is_x86_feature_detected!("avx2") {
+                check_widen_kernel(simd::x86_64::widen_ascii_avx2);
+            }
+        }
+
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                check_widen_kernel(simd::aarch64::widen_ascii_neon);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bulk_encode_matches_per_char() {
+        let samples = [
+            "",
+            "a",
+            "plain ASCII that is long enough to fill several SIMD blocks of input",
+            "mixed: café, naïve, 日本語, 🦀 and back to ASCII for a while longer",
+            "日本語のテキストだけで構成された文字列",
+            "🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀🦀",
+            "0123456789abcdef0123456789abcdef€0123456789abcdef0123456789abcdef",
+            "NUL\0 bytes\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0 are ASCII too",
+        ];
+        for sample in samples {
+            let expected: Vec<u64> = sample.chars().map(unit::encode_char).collect();
+            let units = String64::from(sample).into_units();
+            assert_eq!(units, expected, "{sample:?}");
+            assert_eq!(units.capacity(), units.len(), "{sample:?}");
+
+            let mut s = String64::from("→");
+            s.push_str(sample);
+            assert_eq!(s.as_slice()[1..], expected, "{sample:?}");
+        }
+    }
+
+    fn check_narrow_kernel(write: simd::NarrowAscii, check: simd::NarrowAscii) {
+        use std::mem::MaybeUninit;
+
+        let units: Vec<u64> = (0..100u64).map(|i| (0x20 + i % 90) << 56).collect();
+        for len in 0..units.len() {
+            let mut out = vec![MaybeUninit::uninit(); len];
+            // SAFETY: callers only pass kernels this CPU supports.
+            let n = unsafe { write(&units[..len], &mut out) };
+            assert!(n <= len);
+            for (slot, &unit) in out[..n].iter().zip(&units) {
+                // SAFETY: the kernel initialized the first `n` slots.
+                assert_eq!(unsafe { slot.assume_init() }, (unit >> 56) as u8);
+            }
+            // SAFETY: as above.
+            assert_eq!(unsafe { check(&units[..len], &mut []) }, n);
+        }
+
+        // Stops before the block holding the first unit that is not plain ASCII
+        for bad in [0x41 << 48, 0x80 << 56, (0x41 << 56) | 1, 0x4141 << 48] {
+            let mut mixed = units.clone();
+            mixed[70] = bad;
+            let mut out = vec![MaybeUninit::uninit(); mixed.len()];
+            // SAFETY: as above.
+            assert!(unsafe { write(&mixed, &mut out) } <= 70);
+            // SAFETY: as above.
+            assert!(unsafe { check(&mixed, &mut []) } <= 70);
+        }
+    }
+
+    #[test]
+    fn test_narrow_ascii_kernels() {
+        check_narrow_kernel(
+            simd::narrow_ascii_scalar::<true>,
+            simd::narrow_ascii_scalar::<false>,
+        );
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if std::arch::is_x86_feature_detected!("sse2") {
+                check_narrow_kernel(
+                    simd::x86_64::narrow_ascii_sse2::<true>,
+                    simd::x86_64::narrow_ascii_sse2::<false>,
+                );
+            }
+        }
+
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                check_narrow_kernel(
+                    simd::aarch64::narrow_ascii_neon::<true>,
+                    simd::aarch64::narrow_ascii_neon::<false>,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bulk_decode_reports_first_invalid_unit() {
+        let text = "plain ASCII long enough to span several blocks, then ünïcödé and 🦀 too";
+        let s = String64::from(text);
+        assert_eq!(s.to_string().unwrap(), text);
+        assert!(validate_units(s.as_slice()).is_ok());
+
+        for index in [0, 5, 17, 40, text.chars().count() - 1] {
+            for (bad, kind) in [
+                (0x00410000_00000000, ErrorKind::ZeroLeadByte),
+                (s.as_slice()[index] | 1, ErrorKind::NonZeroReserved),
+                (0x80 << 56, ErrorKind::InvalidLeadByte(0x80)),
+            ] {
+                let mut units = s.as_slice().to_vec();
+                units[index] = bad;
+                let err = validate_units(&units).unwrap_err();
+                assert_eq!((err.kind(), err.index()), (kind, index));
+                let err = Str64::from_raw(&units).to_string().unwrap_err();
+                assert_eq!((err.kind(), err.index()), (kind, index));
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_representations() {
+        use serde_test::{Configure, Token, assert_de_tokens_error, assert_tokens};
+
+        const BYTES: &[u8] = &[
+            0x48, 0, 0, 0, 0, 0, 0, 0, // 'H'
+            0x69, 0, 0, 0, 0, 0, 0, 0, // 'i'
+            0xE2, 0x82, 0xAC, 0, 0, 0, 0, 0, // '€'
+        ];
+        const RESERVED_SET: &[u8] = &[
+            0x48, 0, 0, 0, 0, 0, 0, 0, //
+            0x69, 0, 0, 0, 0, 0, 0, 1, // reserved bit set
+        ];
+
+        let s = String64::from("Hi€");
+        assert_tokens(&s.clone().readable(), &[Token::Str("Hi€")]);
+        assert_tokens(&s.clone().compact(), &[Token::Bytes(BYTES)]);
+
+        // Full validation on the way in, reserved bits included
+        assert_de_tokens_error::<serde_test::Compact<String64>>(
+            &[Token::Bytes(RESERVED_SET)],
+            "invalid UTF64 unit 0x6900000000000001 at index 1: reserved bits must be zero in UTF64 v1.0",
+        );
+        assert_de_tokens_error::<serde_test::Compact<String64>>(
+            &[Token::Bytes(&BYTES[..12])],
+            "input ends with 4 of 8 octets of a unit at index 1",
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json() {
+        let s = String64::from("line\n\"quoted\" 🌍");
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#""line\n\"quoted\" 🌍""#);
+        assert_eq!(serde_json::from_str::<String64>(&json).unwrap(), s);
+        let boxed: Box<Str64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(&*boxed, "line\n\"quoted\" 🌍");
+
+        let nul: String64 = serde_json::from_str(r#""x\u0000""#).unwrap();
+        assert_eq!(nul, "x\0");
+        assert_eq!(serde_json::to_string(&nul).unwrap(), r#""x\u0000""#);
+
+        let mut invalid = s;
+        let units: &mut [u64] = invalid.borrow_mut();
+        units[0] |= 1;
+        assert!(serde_json::to_string(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_ne() {
+        #[repr(C, align(8))]
+        struct Aligned([u8; 24]);
+
+        let s = String64::from("a€😀");
+        let bytes = Aligned(s.to_ne_bytes().try_into().unwrap());
+        let view = Str64::from_bytes_ne(&bytes.0).unwrap();
+        assert_eq!(view, "a€😀");
+        assert_eq!(view.as_slice().as_ptr().cast::<u8>(), bytes.0.as_ptr());
+        assert!(Str64::from_bytes_ne(&[]).unwrap().is_empty());
+
+        let err = Str64::from_bytes_ne(&bytes.0[1..17]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Misaligned);
+        let err = Str64::from_bytes_ne(&bytes.0[..20]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IncompleteUnit { len: 4 });
+        assert_eq!(err.index(), 2);
+
+        let mut bad = Aligned(bytes.0);
+        bad.0[8..16].copy_from_slice(&0x4100_0000_0000_0001u64.to_ne_bytes());
+        let err = Str64::from_bytes_ne(&bad.0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NonZeroReserved);
+        assert_eq!(err.index(), 1);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn test_bytemuck_casts() {
+        let s = String64::from("Hi!");
+        let bytes: &[u8] = bytemuck::cast_slice(s.as_slice());
+        assert_eq!(Str64::from_bytes_ne(bytes).unwrap(), "Hi!");
+
+        let chars: &[Char64] = bytemuck::cast_slice(s.as_slice());
+        assert_eq!(chars[1], Char64::from('i'));
+        let units: &[u64] = bytemuck::TransparentWrapper::peel_slice(chars);
+        assert_eq!(units, s.as_slice());
+        assert_eq!(<Char64 as bytemuck::Zeroable>::zeroed().to_u64(), 0);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_string() {
+        let text = String64::from("Löwe 老虎 🐯");
+        let cases = [
+            (
+                "native",
+                text.to_bytes_with_bom(ByteOrder::NATIVE),
+                ByteOrder::NATIVE,
+            ),
+            (
+                "le",
+                text.to_bytes_with_bom(ByteOrder::LittleEndian),
+                ByteOrder::LittleEndian,
+            ),
+            (
+                "be",
+                text.to_bytes_with_bom(ByteOrder::BigEndian),
+                ByteOrder::BigEndian,
+            ),
+            ("no-bom", text.to_be_bytes(), ByteOrder::BigEndian),
+        ];
+        for (name, bytes, order) in cases {
+            let path = temp_path(name);
+            std::fs::write(&path, bytes).unwrap();
+            // SAFETY: nothing else touches the file.
+            let mapped = unsafe { MappedString64::open(&path) }.unwrap();
+            assert_eq!(mapped.byte_order(), order, "{name}");
+            assert_eq!(mapped, *text, "{name}");
+            assert_eq!(mapped.get(5).unwrap(), '老');
+            assert_eq!(mapped.get(text.len()), None);
+            assert_eq!(mapped.rfind(' '), Some(7));
+            assert_eq!(mapped.chars_lossy().next_back(), Some('🐯'));
+            assert!(mapped.units().eq(text.units()));
+            assert_eq!(&*mapped.slice(5..7), "老虎");
+            assert_eq!(mapped.as_str64().is_some(), order == ByteOrder::NATIVE);
+            assert_eq!(mapped.to_string().unwrap(), "Löwe 老虎 🐯");
+            assert_eq!(format!("{mapped:>12}"), "   Löwe 老虎 🐯");
+            assert_eq!(mapped.units().nth_back(1).unwrap(), ' ');
+            assert_eq!(mapped.chars_lossy().nth_back(2), Some('虎'));
+            assert_eq!(mapped.char_indices().nth_back(2), Some((6, '虎')));
+            assert_eq!(mapped.try_chars().nth(1).unwrap(), Ok('ö'));
+            drop(mapped);
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        let path = temp_path("empty");
+        std::fs::write(&path, []).unwrap();
+        // SAFETY: nothing else touches the file.
+        assert!(unsafe { MappedString64::open(&path) }.unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_string_validation() {
+        use mmap::Validation;
+
+        let mut units = String64::from("Löwe 老虎 🐯").into_units();
+        units[2] |= 1;
+        let bytes: Vec<u8> = units.iter().flat_map(|unit| unit.to_be_bytes()).collect();
+        let path = temp_path("invalid");
+        std::fs::write(&path, &bytes).unwrap();
+
+        // SAFETY: nothing else touches the file.
+        let err = unsafe { MappedString64::open(&path) }.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let inner = err.get_ref().unwrap().downcast_ref::<Utf64Error>().unwrap();
+        assert_eq!(inner.kind(), ErrorKind::NonZeroReserved);
+        assert_eq!(inner.index(), 2);
+
+        // SAFETY: nothing else touches the file.
+        let lazy = unsafe { MappedString64::open_with(&path, Validation::Lazy) }.unwrap();
+        assert_eq!(lazy.get(2).unwrap().to_char(), '\u{FFFD}');
+        assert_eq!(lazy.to_string().unwrap_err().index(), 2);
+        assert_eq!(lazy.try_chars().nth(2).unwrap().unwrap_err().index(), 2);
+        drop(lazy);
+
+        // Errors past the first chunk swapped for validation keep their file index
+        for order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+            let mut units = String64::from("x".repeat(2000).as_str()).into_units();
+            units[1500] |= 1;
+            let bytes: Vec<u8> = core::iter::once(BOM)
+                .chain(units)
+                .flat_map(|unit| match order {
+                    ByteOrder::BigEndian => unit.to_be_bytes(),
+                    ByteOrder::LittleEndian => unit.to_le_bytes(),
+                })
+                .collect();
+            std::fs::write(&path, &bytes).unwrap();
+            // SAFETY: nothing else touches the file.
+            let err = unsafe { MappedString64::open(&path) }.unwrap_err();
+            let inner = err.get_ref().unwrap().downcast_ref::<Utf64Error>().unwrap();
+            assert_eq!(
+                (inner.kind(), inner.index()),
+                (ErrorKind::NonZeroReserved, 1500)
+            );
+            // SAFETY: nothing else touches the file.
+            let lazy = unsafe { MappedString64::open_with(&path, Validation::Lazy) }.unwrap();
+            assert_eq!(lazy.to_string().unwrap_err().index(), 1500);
+        }
+
+        std::fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+        // SAFETY: nothing else touches the file.
+        let err = unsafe { MappedString64::open_with(&path, Validation::Lazy) }.unwrap_err();
+        let inner = err.get_ref().unwrap().downcast_ref::<Utf64Error>().unwrap();
+        assert_eq!(inner.kind(), ErrorKind::IncompleteUnit { len: 5 });
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_string_search() {
+        // Long enough that windows swapped for the search meet inside lines and words
+        let line = "Löwe 老虎 Léopard\r\n🐯 tiger\n";
+        let text = String64::from(line.repeat(300).as_str());
+        let text = text.as_str64();
+        for order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+            let path = temp_path(&format!("search-{order:?}"));
+            std::fs::write(&path, text.to_bytes_with_bom(order)).unwrap();
+            // SAFETY: nothing else touches the file.
+            let mapped = unsafe { MappedString64::open(&path) }.unwrap();
+
+            assert_eq!(mapped.find("tiger\nLöwe"), text.find("tiger\nLöwe"));
+            assert_eq!(mapped.rfind("老虎"), text.rfind("老虎"));
+            assert_eq!(mapped.find('🐯'), text.find('🐯'));
+            assert_eq!(mapped.rfind('\r'), text.rfind('\r'));
+            assert_eq!(mapped.find(&['é', 'g'][..]), text.find(&['é', 'g'][..]));
+            assert_eq!(
+                mapped.rfind(|c: char| !c.is_ascii()),
+                text.rfind(|c: char| !c.is_ascii())
+            );
+            assert_eq!(mapped.find(char::is_uppercase), Some(0));
+            assert_eq!(mapped.find("panther"), None);
+            assert_eq!(mapped.find(""), Some(0));
+            assert_eq!(mapped.rfind(""), Some(text.len()));
+            assert!(mapped.contains(&String64::from("rd\r\n")));
+            assert!(mapped.starts_with("Löwe") && mapped.starts_with('L'));
+            assert!(mapped.ends_with("tiger\n") && mapped.ends_with(char::is_whitespace));
+            assert!(!mapped.starts_with(char::is_lowercase));
+
+            assert!(
+                mapped
+                    .split(' ')
+                    .map(std::borrow::Cow::into_owned)
+                    .eq(text.split(' ').map(Str64::to_string64))
+            );
+            let words = mapped.split(char::is_whitespace).count();
+            assert_eq!(words, text.split(char::is_whitespace).count());
+            let lines: Vec<String64> = mapped.lines().map(std::borrow::Cow::into_owned).collect();
+            assert!(lines.iter().map(|l| l.as_str64()).eq(text.lines()));
+            assert_eq!(lines.len(), 600);
+
+            assert_eq!(mapped.to_string().unwrap(), line.repeat(300));
+            assert_eq!(mapped.to_string(), text.to_string());
+            assert!(mapped.char_indices().eq(text.char_indices()));
+            drop(mapped);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    /// A path in the temporary directory unique to this test process.
+    #[cfg(feature = "mmap")]
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("utf64-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_from_utf16() {
+        for sample in [
+            "",
+            "Hello",
+            "Löwe 老虎 Léopard",
+            "🌍a😀\u{FFFF}\u{10000}\u{10FFFF}",
+        ] {
+            let utf16: Vec<u16> = sample.encode_utf16().collect();
+            let s = String64::from_utf16(&utf16).unwrap();
+            assert_eq!(s, sample);
+            assert_eq!(String64::from_utf16_lossy(&utf16), sample);
+            assert_eq!(s.encode_utf16().collect::<Vec<_>>(), utf16);
+            assert_eq!(s.encode_utf16().size_hint().0, s.len());
+        }
+
+        // A high surrogate followed by a non-surrogate, and a lone trailing one
+        let v = [0x0061, 0xD83C, 0x0062, 0xD83C, 0xDF0D, 0xD800];
+        let err = String64::from_utf16(&v).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidCodePoint(0xD83C));
+        assert_eq!(err.index(), 1);
+        assert_eq!(err.unit(), 0xD83C);
+        assert_eq!(String64::from_utf16_lossy(&v), "a\u{FFFD}b🌍\u{FFFD}");
+        let err = String64::from_utf16(&v[2..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidCodePoint(0xD800));
+        assert_eq!(err.index(), 3);
+    }
+
+    #[test]
+    fn test_from_utf16_bytes() {
+        let text = "a€😀";
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(
+            String64::from_utf16_bytes(&be, ByteOrder::BigEndian).unwrap(),
+            text
+        );
+        assert_eq!(
+            String64::from_utf16_bytes(&le, ByteOrder::LittleEndian).unwrap(),
+            text
+        );
+        assert_eq!(
+            String64::from_utf16_bytes_lossy(&le, ByteOrder::LittleEndian),
+            text
+        );
+
+        let err = String64::from_utf16_bytes(&be[..5], ByteOrder::BigEndian).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IncompleteUtf16);
+        assert_eq!(err.index(), 2);
+        assert_eq!(
+            String64::from_utf16_bytes_lossy(&be[..5], ByteOrder::BigEndian),
+            "a€\u{FFFD}"
+        );
+
+        // The high surrogate of '😀' without its low half
+        let err = String64::from_utf16_bytes(&le[..6], ByteOrder::LittleEndian).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidCodePoint(0xD83D));
+        assert_eq!(err.index(), 2);
+
+        // The earlier unpaired surrogate is reported, not the trailing odd byte
+        let err = String64::from_utf16_bytes(&le[..7], ByteOrder::LittleEndian).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidCodePoint(0xD83D));
+        assert_eq!(err.valid_up_to(), 2);
+    }
+
+    #[test]
+    fn test_encode_utf16_invalid_units() {
+        let mut s = String64::from("a😀");
+        let units: &mut [u64] = s.borrow_mut();
+        units[0] |= 1;
+        let utf16: Vec<u16> = s.encode_utf16().collect();
+        assert_eq!(utf16, [0xFFFD, 0xD83D, 0xDE00]);
+    }
+
+    #[test]
+    fn test_array_string() {
+        let mut s = ArrayString64::<4>::new();
+        assert_eq!(s.capacity(), 4);
+        s.try_push_str("世界").unwrap();
+        s.try_push('!').unwrap();
+        assert_eq!(s, "世界!");
+        assert_eq!(s.remaining_capacity(), 1);
+
+        // Pushing too much is all-or-nothing
+        let err = s.try_push_str("ab").unwrap_err();
+        assert_eq!(err.capacity(), 4);
+        assert_eq!(s, "世界!");
+        s.try_push('?').unwrap();
+        assert!(s.is_full());
+        assert!(s.try_push('x').is_err());
+
+        assert_eq!(s.pop(), Some('?'));
+        s.truncate(1);
+        assert_eq!(s, "世");
+        assert_eq!(s.to_string().unwrap(), "世");
+        assert_eq!(String64::from(s), "世");
+        assert_eq!(format!("{s:?}"), "ArrayString64(\"世\")");
+        s.clear();
+        assert!(s.is_empty());
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn test_array_string_conversions() {
+        use std::fmt::Write;
+
+        let s: ArrayString64<5> = "Hello".parse().unwrap();
+        assert_eq!(s.find('l'), Some(2));
+        assert_eq!(ArrayString64::<5>::try_from(&s[1..]).unwrap(), "ello");
+        assert!(ArrayString64::<4>::try_from("Hello").is_err());
+        assert!(ArrayString64::<4>::try_from(s.as_str64()).is_err());
+
+        let mut t = ArrayString64::<8>::new();
+        write!(t, "{}-{}", 12, 34).unwrap();
+        assert_eq!(t, "12-34");
+        assert!(write!(t, "{}", 5678).is_err());
+        assert_eq!(t, "12-34");
+
+        assert!(
+            ArrayString64::<2>::try_from("ab").unwrap() < ArrayString64::try_from("b").unwrap()
+        );
+    }
+
     #[test]
     fn test_deref() {
         let s = String64::from("Hi");
-        let slice: &[u64] = &*s;  // Deref coercion
+        let slice: &Str64 = &s; // Deref coercion
         assert_eq!(slice.len(), 2);
+        assert_eq!(slice, "Hi");
     }
 
     #[test]
     fn test_deref_mut() {
         let mut s = String64::from("Hi");
-        let slice: &mut [u64] = &mut *s;
+        let slice: &mut Str64 = &mut s;
         assert_eq!(slice.len(), 2);
+        slice.as_slice_mut()[1] |= 1;
+        assert_eq!(s.to_string_lossy(), "H\u{FFFD}");
     }
 
     #[test]
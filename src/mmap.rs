@@ -0,0 +1,821 @@
+//! Memory-mapped UTF64 files, enabled by the `mmap` feature.
+//!
+//! [`MappedString64`] maps a serialized UTF64 file and reads its units straight from
+//! the mapping, so the character at any index is one load away and only the pages
+//! actually touched are read from disk.
+
+use crate::byte_order::ByteOrder;
+use crate::char64::Char64;
+use crate::error::{ErrorKind, Utf64Error};
+use crate::pattern::{CharSearcher, Pattern64, Searcher64};
+use crate::str64::{Str64, pad_chars};
+use crate::string64::String64;
+use crate::unit::{encode_char, validate_unit, validate_units};
+use memmap2::Mmap;
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::File,
+    io,
+    iter::{Enumerate, FusedIterator},
+    ops::{Bound, Range, RangeBounds},
+    path::Path,
+    slice,
+};
+
+/// Units swapped at a time when a file that is not in native byte order is validated,
+/// decoded or searched a piece at a time.
+const SWAP_CHUNK: usize = 512;
+
+/// When the units of a [`MappedString64`] are validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Validation {
+    /// Validate every unit when the file is opened, reading the whole file once.
+    Eager,
+    /// Do not validate up front. Invalid units are reported by the strict APIs such as
+    /// [`MappedString64::to_string`], and read as U+FFFD REPLACEMENT CHARACTER
+    /// everywhere else, as in any other [`Str64`].
+    Lazy,
+}
+
+/// A read-only UTF64 string backed by a memory-mapped file.
+///
+/// The file is read as SPECIFICATION §8.2 describes: a leading byte order mark selects
+/// the byte order and is not part of the string, and a file without one is big-endian.
+///
+/// [`open`] validates every unit, which reads the whole file once; opening with
+/// [`Validation::Lazy`] reads only the BOM. Either way the units are never copied as a
+/// whole: [`get`], [`units`] and [`chars_lossy`] load each unit from the mapping as they
+/// go, swapping it if the file is not in the platform's byte order. A file in native
+/// order can also be viewed in place as a [`Str64`] through [`as_str64`]. For a file in
+/// the other order, [`slice`] and [`to_str64`] copy and swap just the range asked for.
+///
+/// Searching works on the mapping too. For a file not in native byte order, a `char` or
+/// string pattern is byte-swapped to match the file, and any other pattern is run over
+/// small swapped windows.
+///
+/// [`open`]: Self::open
+/// [`get`]: Self::get
+/// [`units`]: Self::units
+/// [`chars_lossy`]: Self::chars_lossy
+/// [`as_str64`]: Self::as_str64
+/// [`slice`]: Self::slice
+/// [`to_str64`]: Self::to_str64
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::{ByteOrder, String64, mmap::MappedString64};
+///
+/// let path = std::env::temp_dir().join("utf64-mmap-doctest.u64");
+/// let text = String64::from("Hello, 世界!");
+/// std::fs::write(&path, text.to_bytes(ByteOrder::BigEndian)).unwrap();
+///
+/// // SAFETY: nothing else modifies the file while it is mapped.
+/// let mapped = unsafe { MappedString64::open(&path) }.unwrap();
+/// assert_eq!(mapped.len(), 10);
+/// assert_eq!(mapped.get(7).unwrap(), '世');
+/// assert_eq!(mapped.find("世界"), Some(7));
+/// assert_eq!(mapped, *text);
+/// # drop(mapped);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct MappedString64 {
+    map: Mmap,
+    /// Byte offset of the first unit, past the BOM if there is one.
+    offset: usize,
+    len: usize,
+    order: ByteOrder,
+}
+
+impl MappedString64 {
+    /// Maps the file at `path`, validating every unit, which reads the whole file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be opened or mapped. A file whose length is not a
+    /// multiple of 8 or that holds an invalid unit fails with
+    /// [`io::ErrorKind::InvalidData`], wrapping the [`Utf64Error`].
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, while
+    /// it is mapped. See [`Mmap::map`].
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        // SAFETY: upheld by the caller.
+        unsafe { Self::open_with(path, Validation::Eager) }
+    }
+
+    /// Maps the file at `path`, validating its units as `validation` says.
+    ///
+    /// # Errors
+    ///
+    /// As for [`open`](Self::open); with [`Validation::Lazy`] the units themselves are
+    /// not checked.
+    ///
+    /// # Safety
+    ///
+    /// As for [`open`](Self::open).
+    pub unsafe fn open_with<P: AsRef<Path>>(path: P, validation: Validation) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: upheld by the caller.
+        let map = unsafe { Mmap::map(&file)? };
+
+        let (order, offset) = match ByteOrder::from_bom(&map) {
+            Some(order) => (order, 8),
+            None => (ByteOrder::BigEndian, 0),
+        };
+        let body = map.len() - offset;
+        if body % 8 != 0 {
+            let error = Utf64Error::new(
+                ErrorKind::IncompleteUnit {
+                    len: (body % 8) as u8,
+                },
+                body / 8,
+                0,
+            );
+            return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+        }
+
+        let mapped = Self {
+            map,
+            offset,
+            len: body / 8,
+            order,
+        };
+        if validation == Validation::Eager {
+            mapped
+                .validate()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        }
+        Ok(mapped)
+    }
+
+    /// Validates every unit, swapping a chunk at a time if the file is not in native
+    /// byte order.
+    fn validate(&self) -> crate::Result<()> {
+        if !self.is_swapped() {
+            return validate_units(self.raw());
+        }
+        self.for_each_swapped_chunk(validate_units)
+    }
+
+    /// Calls `f` on every [`SWAP_CHUNK`] units in turn, swapped to native byte order.
+    ///
+    /// The index of an error from `f` is made relative to the whole file.
+    fn for_each_swapped_chunk<F>(&self, mut f: F) -> crate::Result<()>
+    where
+        F: FnMut(&[u64]) -> crate::Result<()>,
+    {
+        let mut buf = [0; SWAP_CHUNK];
+        for (chunk, units) in self.raw().chunks(SWAP_CHUNK).enumerate() {
+            let buf = &mut buf[..units.len()];
+            for (swapped, unit) in buf.iter_mut().zip(units) {
+                *swapped = unit.swap_bytes();
+            }
+            f(buf).map_err(|error| {
+                Utf64Error::new(
+                    error.kind(),
+                    chunk * SWAP_CHUNK + error.index(),
+                    error.unit(),
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Returns the byte order the file is stored in.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.order
+    }
+
+    /// Returns the number of characters in the file, excluding any BOM.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the file holds no characters.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the character at `index`, or `None` if it is out of bounds.
+    ///
+    /// Loads the one unit from the mapping, so only the page holding it is read.
+    pub fn get(&self, index: usize) -> Option<Char64> {
+        let unit = self.load(*self.raw().get(index)?);
+        Some(*Char64::from_unit_ref(&unit))
+    }
+
+    /// Returns an iterator over the units of the file as [`Char64`] values, in native
+    /// byte order.
+    pub fn units(&self) -> MappedUnits<'_> {
+        MappedUnits {
+            data: self.raw().iter(),
+            swap: self.is_swapped(),
+        }
+    }
+
+    /// Returns an iterator over the characters of the file.
+    ///
+    /// Invalid units are yielded as U+FFFD REPLACEMENT CHARACTER.
+    pub fn chars_lossy(&self) -> MappedChars<'_> {
+        MappedChars {
+            units: self.units(),
+        }
+    }
+
+    /// Returns an iterator over the characters of the file and their indices.
+    ///
+    /// See [`Str64::char_indices`]. Invalid units are yielded as U+FFFD REPLACEMENT
+    /// CHARACTER.
+    pub fn char_indices(&self) -> MappedCharIndices<'_> {
+        MappedCharIndices {
+            data: self.units().enumerate(),
+        }
+    }
+
+    /// Returns an iterator that decodes each unit, yielding an error for invalid ones.
+    ///
+    /// See [`Str64::try_chars`]. Each error carries the index of the unit in the file.
+    pub fn try_chars(&self) -> MappedTryChars<'_> {
+        MappedTryChars {
+            data: self.units().enumerate(),
+        }
+    }
+
+    /// Returns the whole file as a `Str64` in place, or `None` if it is not in native
+    /// byte order.
+    pub fn as_str64(&self) -> Option<&Str64> {
+        (!self.is_swapped()).then(|| Str64::from_raw(self.raw()))
+    }
+
+    /// Returns a range of characters as a `Str64`.
+    ///
+    /// Borrowed from the mapping if the file is in native byte order; otherwise just
+    /// this range is copied out and swapped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is after its end.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Cow<'_, Str64> {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        let units = &self.raw()[bounds];
+        if self.is_swapped() {
+            Cow::Owned(String64::from_raw(
+                units.iter().map(|unit| unit.swap_bytes()).collect(),
+            ))
+        } else {
+            Cow::Borrowed(Str64::from_raw(units))
+        }
+    }
+
+    /// Returns the whole file as a `Str64`, copying it only if it is not in native byte
+    /// order. See [`slice`](Self::slice).
+    pub fn to_str64(&self) -> Cow<'_, Str64> {
+        self.slice(..)
+    }
+
+    /// Returns the index of the first match of `pat`.
+    ///
+    /// See [`Str64::find`].
+    pub fn find<P: Pattern64>(&self, pat: P) -> Option<usize> {
+        MappedSearcher::new(self, pat.into_searcher())
+            .find(self.raw(), 0..self.len)
+            .map(|(start, _)| start)
+    }
+
+    /// Returns the index of the last match of `pat`.
+    ///
+    /// See [`Str64::rfind`].
+    pub fn rfind<P: Pattern64>(&self, pat: P) -> Option<usize> {
+        MappedSearcher::new(self, pat.into_searcher())
+            .rfind(self.raw(), 0..self.len)
+            .map(|(start, _)| start)
+    }
+
+    /// Returns `true` if `pat` matches anywhere in the file.
+    ///
+    /// See [`Str64::contains`].
+    pub fn contains<P: Pattern64>(&self, pat: P) -> bool {
+        self.find(pat).is_some()
+    }
+
+    /// Returns `true` if `pat` matches at the start of the file.
+    ///
+    /// See [`Str64::starts_with`].
+    pub fn starts_with<P: Pattern64>(&self, pat: P) -> bool {
+        MappedSearcher::new(self, pat.into_searcher())
+            .prefix_len(self.raw())
+            .is_some()
+    }
+
+    /// Returns `true` if `pat` matches at the end of the file.
+    ///
+    /// See [`Str64::ends_with`].
+    pub fn ends_with<P: Pattern64>(&self, pat: P) -> bool {
+        MappedSearcher::new(self, pat.into_searcher())
+            .suffix_len(self.raw())
+            .is_some()
+    }
+
+    /// Returns an iterator over the pieces of the file separated by matches of `pat`.
+    ///
+    /// See [`Str64::split`]. Each piece is borrowed or copied as by
+    /// [`slice`](Self::slice).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::{ByteOrder, String64, mmap::MappedString64};
+    ///
+    /// let path = std::env::temp_dir().join("utf64-mmap-split-doctest.u64");
+    /// let text = String64::from("a,b,,c");
+    /// std::fs::write(&path, text.to_bytes_with_bom(ByteOrder::LittleEndian)).unwrap();
+    ///
+    /// // SAFETY: nothing else modifies the file while it is mapped.
+    /// let mapped = unsafe { MappedString64::open(&path) }.unwrap();
+    /// let parts: Vec<String> = mapped.split(',').map(|p| p.to_string()).collect();
+    /// assert_eq!(parts, ["a", "b", "", "c"]);
+    /// # drop(mapped);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn split<P: Pattern64>(&self, pat: P) -> MappedSplit<'_, P::Searcher> {
+        MappedSplit::new(self, pat.into_searcher(), true)
+    }
+
+    /// Returns an iterator over the lines of the file.
+    ///
+    /// See [`Str64::lines`]. Each line is borrowed or copied as by
+    /// [`slice`](Self::slice).
+    pub fn lines(&self) -> MappedLines<'_> {
+        MappedLines {
+            inner: MappedSplit::new(self, '\n'.into_searcher(), false),
+        }
+    }
+
+    /// Decodes the whole file to a standard Rust String.
+    ///
+    /// See [`Str64::to_string`]. A file not in native byte order is swapped and decoded
+    /// a chunk at a time rather than copied first.
+    pub fn to_string(&self) -> crate::Result<String> {
+        if !self.is_swapped() {
+            return Str64::from_raw(self.raw()).to_string();
+        }
+        let mut string = String::with_capacity(self.len);
+        self.for_each_swapped_chunk(|units| {
+            string.push_str(&Str64::from_raw(units).to_string()?);
+            Ok(())
+        })?;
+        Ok(string)
+    }
+
+    /// Returns `true` if units must be swapped as they are loaded.
+    fn is_swapped(&self) -> bool {
+        self.order != ByteOrder::NATIVE
+    }
+
+    /// Converts a unit as stored in the file to native byte order.
+    fn load(&self, unit: u64) -> u64 {
+        if self.is_swapped() {
+            unit.swap_bytes()
+        } else {
+            unit
+        }
+    }
+
+    /// Returns the units as stored in the file, excluding any BOM.
+    fn raw(&self) -> &[u64] {
+        if self.len == 0 {
+            return &[];
+        }
+        // SAFETY: the mapping is page-aligned and `offset` is 0 or 8, so the units are
+        // aligned for `u64`; `len` units past `offset` lie within the mapping, which
+        // lives as long as `self`, and every bit pattern is a valid `u64`.
+        unsafe { slice::from_raw_parts(self.map.as_ptr().add(self.offset).cast::<u64>(), self.len) }
+    }
+}
+
+/// Shows the length and byte order rather than the text, which may be huge.
+impl fmt::Debug for MappedString64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedString64")
+            .field("len", &self.len)
+            .field("byte_order", &self.order)
+            .finish()
+    }
+}
+
+/// Writes the text as [`Str64`]'s `Display` does, loading one unit at a time.
+impl fmt::Display for MappedString64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad_chars(f, self.chars_lossy())
+    }
+}
+
+impl PartialEq<str> for MappedString64 {
+    fn eq(&self, other: &str) -> bool {
+        self.units()
+            .map(u64::from)
+            .eq(other.chars().map(encode_char))
+    }
+}
+
+impl PartialEq<&str> for MappedString64 {
+    fn eq(&self, other: &&str) -> bool {
+        self.eq(*other)
+    }
+}
+
+impl PartialEq<Str64> for MappedString64 {
+    fn eq(&self, other: &Str64) -> bool {
+        self.units().eq(other.units())
+    }
+}
+
+/// An iterator over the units of a [`MappedString64`].
+///
+/// Created by [`MappedString64::units`].
+#[derive(Clone, Debug)]
+pub struct MappedUnits<'a> {
+    data: slice::Iter<'a, u64>,
+    swap: bool,
+}
+
+impl MappedUnits<'_> {
+    fn load(&self, unit: &u64) -> Char64 {
+        let unit = if self.swap { unit.swap_bytes() } else { *unit };
+        *Char64::from_unit_ref(&unit)
+    }
+}
+
+impl Iterator for MappedUnits<'_> {
+    type Item = Char64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let unit = self.data.next()?;
+        Some(self.load(unit))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let unit = self.data.nth(n)?;
+        Some(self.load(unit))
+    }
+}
+
+impl DoubleEndedIterator for MappedUnits<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let unit = self.data.next_back()?;
+        Some(self.load(unit))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let unit = self.data.nth_back(n)?;
+        Some(self.load(unit))
+    }
+}
+
+impl ExactSizeIterator for MappedUnits<'_> {}
+
+impl FusedIterator for MappedUnits<'_> {}
+
+/// An iterator over the characters of a [`MappedString64`].
+///
+/// Created by [`MappedString64::chars_lossy`].
+#[derive(Clone, Debug)]
+pub struct MappedChars<'a> {
+    units: MappedUnits<'a>,
+}
+
+impl Iterator for MappedChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.units.next().map(Char64::to_char)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.units.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.units.nth(n).map(Char64::to_char)
+    }
+}
+
+impl DoubleEndedIterator for MappedChars<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.units.next_back().map(Char64::to_char)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.units.nth_back(n).map(Char64::to_char)
+    }
+}
+
+impl ExactSizeIterator for MappedChars<'_> {}
+
+impl FusedIterator for MappedChars<'_> {}
+
+/// An iterator over the characters of a [`MappedString64`] and their indices.
+///
+/// Created by [`MappedString64::char_indices`].
+#[derive(Clone, Debug)]
+pub struct MappedCharIndices<'a> {
+    data: Enumerate<MappedUnits<'a>>,
+}
+
+impl MappedCharIndices<'_> {
+    fn decode((index, unit): (usize, Char64)) -> (usize, char) {
+        (index, unit.to_char())
+    }
+}
+
+impl Iterator for MappedCharIndices<'_> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(Self::decode)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth(n).map(Self::decode)
+    }
+}
+
+impl DoubleEndedIterator for MappedCharIndices<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back().map(Self::decode)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth_back(n).map(Self::decode)
+    }
+}
+
+impl ExactSizeIterator for MappedCharIndices<'_> {}
+
+impl FusedIterator for MappedCharIndices<'_> {}
+
+/// An iterator that decodes the units of a [`MappedString64`], yielding an error for
+/// invalid ones.
+///
+/// Created by [`MappedString64::try_chars`].
+#[derive(Clone, Debug)]
+pub struct MappedTryChars<'a> {
+    data: Enumerate<MappedUnits<'a>>,
+}
+
+impl MappedTryChars<'_> {
+    fn decode((index, unit): (usize, Char64)) -> crate::Result<char> {
+        let unit = u64::from(unit);
+        validate_unit(unit).map_err(|kind| Utf64Error::new(kind, index, unit))
+    }
+}
+
+impl Iterator for MappedTryChars<'_> {
+    type Item = crate::Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(Self::decode)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth(n).map(Self::decode)
+    }
+}
+
+impl DoubleEndedIterator for MappedTryChars<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back().map(Self::decode)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth_back(n).map(Self::decode)
+    }
+}
+
+impl ExactSizeIterator for MappedTryChars<'_> {}
+
+impl FusedIterator for MappedTryChars<'_> {}
+
+/// An iterator over the pieces of a [`MappedString64`] between matches of a pattern.
+///
+/// Created by [`MappedString64::split`].
+pub struct MappedSplit<'a, S> {
+    mapped: &'a MappedString64,
+    searcher: MappedSearcher<S>,
+    /// Start of the next piece.
+    start: usize,
+    /// Where the search resumes; past the end once the last match was empty there.
+    position: usize,
+    allow_trailing_empty: bool,
+    finished: bool,
+}
+
+impl<'a, S: Searcher64> MappedSplit<'a, S> {
+    fn new(mapped: &'a MappedString64, searcher: S, allow_trailing_empty: bool) -> Self {
+        Self {
+            mapped,
+            searcher: MappedSearcher::new(mapped, searcher),
+            start: 0,
+            position: 0,
+            allow_trailing_empty,
+            finished: false,
+        }
+    }
+
+    /// Returns the range of the next piece.
+    fn next_range(&mut self) -> Option<Range<usize>> {
+        if self.finished {
+            return None;
+        }
+
+        let end = self.mapped.len;
+        let found = if self.position <= end {
+            self.searcher.find(self.mapped.raw(), self.position..end)
+        } else {
+            None
+        };
+        let Some((start, len)) = found else {
+            self.finished = true;
+            return (self.allow_trailing_empty || end > self.start).then_some(self.start..end);
+        };
+
+        let piece = self.start..start;
+        self.start = start + len;
+        // An empty match must still make progress
+        self.position = start + len.max(1);
+        Some(piece)
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for MappedSplit<'a, S> {
+    type Item = Cow<'a, Str64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = self.next_range()?;
+        Some(self.mapped.slice(range))
+    }
+}
+
+/// An iterator over the lines of a [`MappedString64`].
+///
+/// Created by [`MappedString64::lines`].
+pub struct MappedLines<'a> {
+    inner: MappedSplit<'a, CharSearcher>,
+}
+
+impl<'a> Iterator for MappedLines<'a> {
+    type Item = Cow<'a, Str64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = self.inner.next_range()?;
+        let mapped = self.inner.mapped;
+        // Only a `\r` directly before a `\n` is part of the line ending
+        if !self.inner.finished
+            && line.end > line.start
+            && mapped.get(line.end - 1).is_some_and(|ch| ch == '\r')
+        {
+            line.end -= 1;
+        }
+        Some(mapped.slice(line))
+    }
+}
+
+/// A searcher prepared for the units of a [`MappedString64`] as stored in the file.
+struct MappedSearcher<S> {
+    searcher: S,
+    mode: SearchMode,
+}
+
+/// How a [`MappedSearcher`] sees the units in native byte order.
+enum SearchMode {
+    /// The file is in native byte order, or the searcher was switched to its order.
+    Raw,
+    /// Units are swapped a window at a time, neighbouring windows sharing `overlap`
+    /// units so that every match lies whole in one of them.
+    Windowed { overlap: usize },
+    /// The searcher puts no bound on the length of a match, so the whole file was
+    /// swapped up front.
+    Copied(Vec<u64>),
+}
+
+impl<S: Searcher64> MappedSearcher<S> {
+    fn new(mapped: &MappedString64, mut searcher: S) -> Self {
+        let mode = if !mapped.is_swapped() || searcher.swap_byte_order() {
+            SearchMode::Raw
+        } else if let Some(max_len) = searcher.max_match_len() {
+            SearchMode::Windowed {
+                overlap: max_len.saturating_sub(1),
+            }
+        } else {
+            SearchMode::Copied(mapped.raw().iter().map(|unit| unit.swap_bytes()).collect())
+        };
+        Self { searcher, mode }
+    }
+
+    /// Returns the start and length of the first match in `range` of `raw`.
+    fn find(&mut self, raw: &[u64], range: Range<usize>) -> Option<(usize, usize)> {
+        let overlap = match &self.mode {
+            SearchMode::Raw => return rebase(range.start, self.searcher.find_in(&raw[range])),
+            SearchMode::Copied(units) => {
+                return rebase(range.start, self.searcher.find_in(&units[range]));
+            }
+            SearchMode::Windowed { overlap } => *overlap,
+        };
+
+        let mut buf = Vec::with_capacity(SWAP_CHUNK + overlap);
+        let mut start = range.start;
+        loop {
+            let end = (start + SWAP_CHUNK + overlap).min(range.end);
+            swap_into(&mut buf, &raw[start..end]);
+            let last = end == range.end;
+            // A match starting in the overlap may be cut short; the next window has it
+            match self.searcher.find_in(&buf) {
+                Some((offset, len)) if offset < SWAP_CHUNK || last => {
+                    return Some((start + offset, len));
+                }
+                _ if last => return None,
+                _ => start += SWAP_CHUNK,
+            }
+        }
+    }
+
+    /// Returns the start and length of the last match in `range` of `raw`.
+    fn rfind(&mut self, raw: &[u64], range: Range<usize>) -> Option<(usize, usize)> {
+        let overlap = match &self.mode {
+            SearchMode::Raw => return rebase(range.start, self.searcher.rfind_in(&raw[range])),
+            SearchMode::Copied(units) => {
+                return rebase(range.start, self.searcher.rfind_in(&units[range]));
+            }
+            SearchMode::Windowed { overlap } => *overlap,
+        };
+
+        let mut buf = Vec::with_capacity(SWAP_CHUNK + overlap);
+        let mut end = range.end;
+        loop {
+            let start = end.saturating_sub(SWAP_CHUNK + overlap).max(range.start);
+            swap_into(&mut buf, &raw[start..end]);
+            let first = start == range.start;
+            // A match ending in the overlap may be cut short; the next window has it
+            match self.searcher.rfind_in(&buf) {
+                Some((offset, len)) if start + offset + len + SWAP_CHUNK > end || first => {
+                    return Some((start + offset, len));
+                }
+                _ if first => return None,
+                _ => end -= SWAP_CHUNK,
+            }
+        }
+    }
+
+    /// Returns the length of the match at the start of `raw`, if any.
+    fn prefix_len(&mut self, raw: &[u64]) -> Option<usize> {
+        match &self.mode {
+            SearchMode::Raw => self.searcher.prefix_len(raw),
+            SearchMode::Copied(units) => self.searcher.prefix_len(units),
+            SearchMode::Windowed { overlap } => {
+                let mut buf = Vec::new();
+                swap_into(&mut buf, &raw[..raw.len().min(overlap + 1)]);
+                self.searcher.prefix_len(&buf)
+            }
+        }
+    }
+
+    /// Returns the length of the match at the end of `raw`, if any.
+    fn suffix_len(&mut self, raw: &[u64]) -> Option<usize> {
+        match &self.mode {
+            SearchMode::Raw => self.searcher.suffix_len(raw),
+            SearchMode::Copied(units) => self.searcher.suffix_len(units),
+            SearchMode::Windowed { overlap } => {
+                let mut buf = Vec::new();
+                swap_into(&mut buf, &raw[raw.len().saturating_sub(overlap + 1)..]);
+                self.searcher.suffix_len(&buf)
+            }
+        }
+    }
+}
+
+/// Offsets a match found in a sub-slice starting at `start`.
+fn rebase(start: usize, found: Option<(usize, usize)>) -> Option<(usize, usize)> {
+    found.map(|(offset, len)| (start + offset, len))
+}
+
+/// Replaces the contents of `buf` with `units` swapped to the other byte order.
+fn swap_into(buf: &mut Vec<u64>, units: &[u64]) {
+    buf.clear();
+    buf.extend(units.iter().map(|unit| unit.swap_bytes()));
+}
@@ -0,0 +1,816 @@
+//! Patterns for searching UTF64 strings.
+//!
+//! [`Pattern64`] is the UTF64 counterpart of `str`'s pattern API: it is implemented for
+//! `char`, string slices, UTF64 strings, slices of `char` and `FnMut(char) -> bool`
+//! closures, and drives the search and splitting methods on [`Str64`].
+//! All positions are character indices. This module also holds the iterators those
+//! methods return.
+
+use crate::str64::Str64;
+#[cfg(feature = "alloc")]
+use crate::string64::String64;
+use crate::unit::{decode_lossy, encode_char};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String};
+
+/// A pattern that can be searched for in a [`Str64`].
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::String64;
+///
+/// let s = String64::from("Löwe 老虎 Léopard");
+/// assert_eq!(s.find('é'), Some(9));
+/// assert_eq!(s.find("老虎"), Some(5));
+/// assert_eq!(s.find(&['x', 'L'][..]), Some(0));
+/// assert_eq!(s.rfind(char::is_whitespace), Some(7));
+/// ```
+pub trait Pattern64 {
+    /// The searcher this pattern turns into.
+    type Searcher: Searcher64;
+
+    /// Prepares this pattern for searching.
+    fn into_searcher(self) -> Self::Searcher;
+}
+
+/// Finds occurrences of a prepared [`Pattern64`] in a run of UTF64 units.
+///
+/// Every method returns positions relative to `haystack`, in units.
+pub trait Searcher64 {
+    /// Returns the start and length of the first match.
+    fn find_in(&mut self, haystack: &[u64]) -> Option<(usize, usize)>;
+
+    /// Returns the start and length of the last match.
+    fn rfind_in(&mut self, haystack: &[u64]) -> Option<(usize, usize)>;
+
+    /// Returns the length of the match at the start of `haystack`, if any.
+    fn prefix_len(&mut self, haystack: &[u64]) -> Option<usize>;
+
+    /// Returns the length of the match at the end of `haystack`, if any.
+    fn suffix_len(&mut self, haystack: &[u64]) -> Option<usize>;
+
+    /// Returns the most units a single match can span, or `None` if there is no bound.
+    ///
+    /// With a bound, a long haystack can be searched a window at a time, as long as
+    /// neighbouring windows overlap by one unit less than the bound. The default is
+    /// `None`.
+    fn max_match_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Switches this searcher to haystacks whose units are byte-swapped, returning
+    /// `false` if it cannot.
+    ///
+    /// Only searchers that compare whole units for equality can do this. The default
+    /// returns `false`.
+    fn swap_byte_order(&mut self) -> bool {
+        false
+    }
+}
+
+/// Searcher for a single `char`.
+#[derive(Debug, Clone)]
+pub struct CharSearcher {
+    unit: u64,
+}
+
+/// Searcher for any of a set of `char`s.
+#[derive(Debug, Clone)]
+pub struct CharSliceSearcher<'a> {
+    chars: &'a [char],
+}
+
+/// Searcher for characters matching a predicate.
+///
+/// Invalid units are passed to the predicate as U+FFFD REPLACEMENT CHARACTER.
+#[derive(Debug, Clone)]
+pub struct CharPredicateSearcher<F> {
+    predicate: F,
+}
+
+/// Searcher for a substring.
+///
+/// Uses the Two-Way algorithm over whole units, which runs in linear time and constant
+/// space whatever the needle. A 64-bit set of unit hashes lets the search skip a whole
+/// needle length past units that do not occur in the needle.
+#[derive(Debug, Clone)]
+pub struct StrSearcher<'a> {
+    needle: Needle<'a>,
+    /// Start of the right half of the critical factorization used by `find_in`.
+    crit_pos: usize,
+    /// Start of the right half of the critical factorization used by `rfind_in`.
+    crit_pos_back: usize,
+    /// Shift after the left half mismatches.
+    period: usize,
+    /// Whether `period` is the needle's true period; if not, matched prefixes are not
+    /// remembered between shifts.
+    long_period: bool,
+    unitset: u64,
+}
+
+/// The units a [`StrSearcher`] looks for, encoded from a `str` pattern when there is an
+/// allocator and borrowed from a UTF64 pattern otherwise.
+#[cfg(feature = "alloc")]
+type Needle<'a> = Cow<'a, [u64]>;
+#[cfg(not(feature = "alloc"))]
+type Needle<'a> = &'a [u64];
+
+impl<'a> StrSearcher<'a> {
+    fn new(needle: Needle<'a>) -> Self {
+        let mut searcher = Self {
+            needle,
+            crit_pos: 0,
+            crit_pos_back: 0,
+            period: 1,
+            long_period: true,
+            unitset: 0,
+        };
+        let units = searcher.needle();
+        let len = units.len();
+        let (crit_pos, period) = if len == 0 {
+            (0, 1)
+        } else {
+            let (crit_less, period_less) = maximal_suffix(units, false);
+            let (crit_greater, period_greater) = maximal_suffix(units, true);
+            if crit_less > crit_greater {
+                (crit_less, period_less)
+            } else {
+                (crit_greater, period_greater)
+            }
+        };
+
+        let short_period =
+            period + crit_pos <= len && units[..crit_pos] == units[period..period + crit_pos];
+        let (crit_pos_back, period, unitset) = if short_period {
+            let crit_pos_back = len
+                - reverse_maximal_suffix(units, period, false)
+                    .max(reverse_maximal_suffix(units, period, true));
+            (crit_pos_back, period, unitset(&units[..period]))
+        } else {
+            (crit_pos, crit_pos.max(len - crit_pos) + 1, unitset(units))
+        };
+
+        searcher.crit_pos = crit_pos;
+        searcher.crit_pos_back = crit_pos_back;
+        searcher.period = period;
+        searcher.long_period = !short_period;
+        searcher.unitset = unitset;
+        searcher
+    }
+
+    /// Returns whether `unit` may occur in the needle.
+    fn may_contain(&self, unit: u64) -> bool {
+        (self.unitset >> (bucket(unit) % 64)) & 1 != 0
+    }
+
+    fn borrowed(needle: &'a [u64]) -> Self {
+        #[cfg(feature = "alloc")]
+        let needle = Cow::Borrowed(needle);
+        Self::new(needle)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn needle(&self) -> &[u64] {
+        &self.needle
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn needle(&self) -> &[u64] {
+        self.needle
+    }
+}
+
+/// Hashes a unit into one of 256 buckets.
+fn bucket(unit: u64) -> usize {
+    let upper = (unit >> 32) as u32;
+    (upper ^ (upper >> 8) ^ (upper >> 16) ^ (upper >> 24)) as u8 as usize
+}
+
+/// Returns the set of unit hashes in `units`, one bit per bucket modulo 64.
+fn unitset(units: &[u64]) -> u64 {
+    units
+        .iter()
+        .fold(0, |set, &unit| set | 1 << (bucket(unit) % 64))
+}
+
+/// Returns the start and period of the maximal suffix of `units` under the unit order,
+/// or its reverse when `order_greater` is set.
+fn maximal_suffix(units: &[u64], order_greater: bool) -> (usize, usize) {
+    let mut left = 0;
+    let mut right = 1;
+    let mut offset = 0;
+    let mut period = 1;
+
+    while let Some(&a) = units.get(right + offset) {
+        let b = units[left + offset];
+        if (a < b && !order_greater) || (a > b && order_greater) {
+            right += offset + 1;
+            offset = 0;
+            period = right - left;
+        } else if a == b {
+            if offset + 1 == period {
+                right += offset + 1;
+                offset = 0;
+            } else {
+                offset += 1;
+            }
+        } else {
+            left = right;
+            right += 1;
+            offset = 0;
+            period = 1;
+        }
+    }
+    (left, period)
+}
+
+/// Returns the length of the maximal suffix of the reversed `units`, stopping once
+/// `known_period` is reached.
+fn reverse_maximal_suffix(units: &[u64], known_period: usize, order_greater: bool) -> usize {
+    let mut left = 0;
+    let mut right = 1;
+    let mut offset = 0;
+    let mut period = 1;
+    let n = units.len();
+
+    while right + offset < n {
+        let a = units[n - (1 + right + offset)];
+        let b = units[n - (1 + left + offset)];
+        if (a < b && !order_greater) || (a > b && order_greater) {
+            right += offset + 1;
+            offset = 0;
+            period = right - left;
+        } else if a == b {
+            if offset + 1 == period {
+                right += offset + 1;
+                offset = 0;
+            } else {
+                offset += 1;
+            }
+        } else {
+            left = right;
+            right += 1;
+            offset = 0;
+            period = 1;
+        }
+        if period == known_period {
+            break;
+        }
+    }
+    left
+}
+
+impl Searcher64 for StrSearcher<'_> {
+    fn find_in(&mut self, haystack: &[u64]) -> Option<(usize, usize)> {
+        let needle = self.needle();
+        let len = needle.len();
+        if len > haystack.len() {
+            return None;
+        }
+        if len == 0 {
+            return Some((0, 0));
+        }
+
+        // With a short period, `memory` counts the needle units already known to match
+        // at `position` after shifting by the period.
+        let mut position = 0;
+        let mut memory = 0;
+        while let Some(&last) = haystack.get(position + len - 1) {
+            if !self.may_contain(last) {
+                position += len;
+                memory = 0;
+                continue;
+            }
+
+            let start = if self.long_period {
+                self.crit_pos
+            } else {
+                self.crit_pos.max(memory)
+            };
+            if let Some(i) = (start..len).find(|&i| needle[i] != haystack[position + i]) {
+                position += i - self.crit_pos + 1;
+                memory = 0;
+                continue;
+            }
+
+            let start = if self.long_period { 0 } else { memory };
+            if (start..self.crit_pos)
+                .rev()
+                .any(|i| needle[i] != haystack[position + i])
+            {
+                position += self.period;
+                if !self.long_period {
+                    memory = len - self.period;
+                }
+                continue;
+            }
+            return Some((position, len));
+        }
+        None
+    }
+
+    fn rfind_in(&mut self, haystack: &[u64]) -> Option<(usize, usize)> {
+        let needle = self.needle();
+        let len = needle.len();
+        if len > haystack.len() {
+            return None;
+        }
+
+        // Mirror of `find_in`: `memory` is where the suffix known to match begins.
+        let mut end = haystack.len();
+        let mut memory = len;
+        while let Some(start) = end.checked_sub(len) {
+            if len == 0 {
+                return Some((start, 0));
+            }
+            if !self.may_contain(haystack[start]) {
+                end = start;
+                memory = len;
+                continue;
+            }
+
+            let crit = if self.long_period {
+                self.crit_pos_back
+            } else {
+                self.crit_pos_back.min(memory)
+            };
+            if let Some(i) = (0..crit).rev().find(|&i| needle[i] != haystack[start + i]) {
+                end -= self.crit_pos_back - i;
+                memory = len;
+                continue;
+            }
+
+            let needle_end = if self.long_period { len } else { memory };
+            if (self.crit_pos_back..needle_end).any(|i| needle[i] != haystack[start + i]) {
+                end -= self.period;
+                if !self.long_period {
+                    memory = self.period;
+                }
+                continue;
+            }
+            return Some((start, len));
+        }
+        None
+    }
+    fn prefix_len(&mut self, haystack: &[u64]) -> Option<usize> {
+        haystack
+            .starts_with(self.needle())
+            .then_some(self.needle().len())
+    }
+
+    fn suffix_len(&mut self, haystack: &[u64]) -> Option<usize> {
+        haystack
+            .ends_with(self.needle())
+            .then_some(self.needle().len())
+    }
+
+    fn max_match_len(&self) -> Option<usize> {
+        Some(self.needle().len())
+    }
+
+    #[cfg(feature = "alloc")]
+    fn swap_byte_order(&mut self) -> bool {
+        let swapped = self.needle().iter().map(|unit| unit.swap_bytes()).collect();
+        *self = StrSearcher::new(Cow::Owned(swapped));
+        true
+    }
+}
+
+/// Implements [`Searcher64`] for a searcher that matches one unit at a time.
+macro_rules! impl_unit_searcher {
+    (
+        $searcher:ty $(where $generic:ident: $bound:path)?,
+        |$this:ident, $unit:ident| $matches:expr
+        $(, swap: |$swap_this:ident| $swap:expr)?
+    ) => {
+        impl$(<$generic: $bound>)? Searcher64 for $searcher {
+            fn find_in(&mut self, haystack: &[u64]) -> Option<(usize, usize)> {
+                let $this = self;
+                haystack.iter().position(|&$unit| $matches).map(|i| (i, 1))
+            }
+
+            fn rfind_in(&mut self, haystack: &[u64]) -> Option<(usize, usize)> {
+                let $this = self;
+                haystack.iter().rposition(|&$unit| $matches).map(|i| (i, 1))
+            }
+
+            fn prefix_len(&mut self, haystack: &[u64]) -> Option<usize> {
+                let $this = self;
+                let &$unit = haystack.first()?;
+                ($matches).then_some(1)
+            }
+
+            fn suffix_len(&mut self, haystack: &[u64]) -> Option<usize> {
+                let $this = self;
+                let &$unit = haystack.last()?;
+                ($matches).then_some(1)
+            }
+
+            fn max_match_len(&self) -> Option<usize> {
+                Some(1)
+            }
+
+            $(
+                fn swap_byte_order(&mut self) -> bool {
+                    let $swap_this = self;
+                    $swap
+                }
+            )?
+        }
+    };
+}
+
+impl_unit_searcher!(CharSearcher, |this, unit| unit == this.unit, swap: |this| {
+    this.unit = this.unit.swap_bytes();
+    true
+});
+impl_unit_searcher!(CharSliceSearcher<'_>, |this, unit| {
+    this.chars.contains(&decode_lossy(unit))
+});
+impl_unit_searcher!(CharPredicateSearcher<F> where F: FnMut(char) -> bool, |this, unit| {
+    (this.predicate)(decode_lossy(unit))
+});
+
+impl Pattern64 for char {
+    type Searcher = CharSearcher;
+
+    fn into_searcher(self) -> Self::Searcher {
+        CharSearcher {
+            unit: encode_char(self),
+        }
+    }
+}
+
+impl<'a> Pattern64 for &'a [char] {
+    type Searcher = CharSliceSearcher<'a>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        CharSliceSearcher { chars: self }
+    }
+}
+
+impl<'a, const N: usize> Pattern64 for &'a [char; N] {
+    type Searcher = CharSliceSearcher<'a>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        CharSliceSearcher { chars: self }
+    }
+}
+
+impl<F: FnMut(char) -> bool> Pattern64 for F {
+    type Searcher = CharPredicateSearcher<F>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        CharPredicateSearcher { predicate: self }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Pattern64 for &str {
+    type Searcher = StrSearcher<'static>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        StrSearcher::new(Cow::Owned(self.chars().map(encode_char).collect()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Pattern64 for &String {
+    type Searcher = StrSearcher<'static>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        self.as_str().into_searcher()
+    }
+}
+
+impl<'a> Pattern64 for &'a Str64 {
+    type Searcher = StrSearcher<'a>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        StrSearcher::borrowed(self.as_slice())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Pattern64 for &'a String64 {
+    type Searcher = StrSearcher<'a>;
+
+    fn into_searcher(self) -> Self::Searcher {
+        StrSearcher::borrowed(self.as_slice())
+    }
+}
+
+/// An iterator over the non-overlapping matches of a pattern, with their indices.
+///
+/// Created by [`Str64::match_indices`].
+pub struct MatchIndices<'a, S> {
+    haystack: &'a Str64,
+    searcher: S,
+    position: usize,
+}
+
+impl<'a, S: Searcher64> MatchIndices<'a, S> {
+    pub(crate) fn new(haystack: &'a Str64, searcher: S) -> Self {
+        Self {
+            haystack,
+            searcher,
+            position: 0,
+        }
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for MatchIndices<'a, S> {
+    type Item = (usize, &'a Str64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.haystack.as_slice().get(self.position..)?;
+        let (offset, len) = self.searcher.find_in(rest)?;
+        let start = self.position + offset;
+
+        // An empty match must still make progress
+        self.position = start + len.max(1);
+        Some((start, &self.haystack[start..start + len]))
+    }
+}
+
+/// An iterator over the non-overlapping matches of a pattern.
+///
+/// Created by [`Str64::matches`].
+pub struct Matches<'a, S> {
+    inner: MatchIndices<'a, S>,
+}
+
+impl<'a, S: Searcher64> Matches<'a, S> {
+    pub(crate) fn new(haystack: &'a Str64, searcher: S) -> Self {
+        Self {
+            inner: MatchIndices::new(haystack, searcher),
+        }
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for Matches<'a, S> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, matched)| matched)
+    }
+}
+
+/// The search state shared by the splitting iterators.
+struct SplitInternal<'a, S> {
+    haystack: &'a Str64,
+    searcher: S,
+    /// Start of the next piece from the front.
+    start: usize,
+    /// End of the next piece from the back.
+    end: usize,
+    /// Where the forward search resumes; past `end` once the front is exhausted.
+    position: usize,
+    /// Where the backward search stops; `None` once the back is exhausted.
+    back_position: Option<usize>,
+    allow_trailing_empty: bool,
+    finished: bool,
+}
+
+impl<'a, S: Searcher64> SplitInternal<'a, S> {
+    fn new(haystack: &'a Str64, searcher: S, allow_trailing_empty: bool) -> Self {
+        Self {
+            haystack,
+            searcher,
+            start: 0,
+            end: haystack.len(),
+            position: 0,
+            back_position: Some(haystack.len()),
+            allow_trailing_empty,
+            finished: false,
+        }
+    }
+
+    /// Returns the piece between the last match and the end, once.
+    fn get_end(&mut self) -> Option<&'a Str64> {
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+        (self.allow_trailing_empty || self.end > self.start)
+            .then(|| &self.haystack[self.start..self.end])
+    }
+
+    fn next(&mut self) -> Option<&'a Str64> {
+        if self.finished {
+            return None;
+        }
+
+        let found = match self.haystack.as_slice().get(self.position..self.end) {
+            Some(rest) => self.searcher.find_in(rest),
+            None => None,
+        };
+        let Some((offset, len)) = found else {
+            return self.get_end();
+        };
+
+        let start = self.position + offset;
+        let piece = &self.haystack[self.start..start];
+        self.start = start + len;
+        // An empty match must still make progress
+        self.position = start + len.max(1);
+        Some(piece)
+    }
+
+    fn next_back(&mut self) -> Option<&'a Str64> {
+        if self.finished {
+            return None;
+        }
+
+        if !self.allow_trailing_empty {
+            self.allow_trailing_empty = true;
+            match self.next_back() {
+                Some(piece) if !piece.is_empty() => return Some(piece),
+                _ if self.finished => return None,
+                _ => {}
+            }
+        }
+
+        let found = match self
+            .back_position
+            .and_then(|end| self.haystack.as_slice().get(self.start..end))
+        {
+            Some(rest) => self.searcher.rfind_in(rest),
+            None => None,
+        };
+        let Some((offset, len)) = found else {
+            return self.get_end();
+        };
+
+        let start = self.start + offset;
+        let piece = &self.haystack[start + len..self.end];
+        self.end = start;
+        // An empty match must still make progress
+        self.back_position = if len == 0 {
+            start.checked_sub(1)
+        } else {
+            Some(start)
+        };
+        Some(piece)
+    }
+}
+
+/// An iterator over the substrings between matches of a pattern.
+///
+/// Created by [`Str64::split`].
+pub struct Split<'a, S> {
+    inner: SplitInternal<'a, S>,
+}
+
+impl<'a, S: Searcher64> Split<'a, S> {
+    pub(crate) fn new(haystack: &'a Str64, searcher: S) -> Self {
+        Self {
+            inner: SplitInternal::new(haystack, searcher, true),
+        }
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for Split<'a, S> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+/// An iterator over the substrings between matches of a pattern, from the end.
+///
+/// Created by [`Str64::rsplit`].
+pub struct RSplit<'a, S> {
+    inner: SplitInternal<'a, S>,
+}
+
+impl<'a, S: Searcher64> RSplit<'a, S> {
+    pub(crate) fn new(haystack: &'a Str64, searcher: S) -> Self {
+        Self {
+            inner: SplitInternal::new(haystack, searcher, true),
+        }
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for RSplit<'a, S> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// An iterator over at most `n` substrings between matches of a pattern.
+///
+/// Created by [`Str64::splitn`].
+pub struct SplitN<'a, S> {
+    inner: SplitInternal<'a, S>,
+    count: usize,
+}
+
+impl<'a, S: Searcher64> SplitN<'a, S> {
+    pub(crate) fn new(haystack: &'a Str64, searcher: S, count: usize) -> Self {
+        Self {
+            inner: SplitInternal::new(haystack, searcher, true),
+            count,
+        }
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for SplitN<'a, S> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.count {
+            0 => None,
+            1 => {
+                self.count = 0;
+                self.inner.get_end()
+            }
+            _ => {
+                self.count -= 1;
+                self.inner.next()
+            }
+        }
+    }
+}
+
+/// An iterator over the substrings terminated by matches of a pattern.
+///
+/// Created by [`Str64::split_terminator`].
+pub struct SplitTerminator<'a, S> {
+    inner: SplitInternal<'a, S>,
+}
+
+impl<'a, S: Searcher64> SplitTerminator<'a, S> {
+    pub(crate) fn new(haystack: &'a Str64, searcher: S) -> Self {
+        Self {
+            inner: SplitInternal::new(haystack, searcher, false),
+        }
+    }
+}
+
+impl<'a, S: Searcher64> Iterator for SplitTerminator<'a, S> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+/// An iterator over the whitespace-separated words of a string.
+///
+/// Created by [`Str64::split_whitespace`].
+pub struct SplitWhitespace<'a> {
+    inner: SplitInternal<'a, WhitespaceSearcher>,
+}
+
+type WhitespaceSearcher = CharPredicateSearcher<fn(char) -> bool>;
+
+impl<'a> SplitWhitespace<'a> {
+    pub(crate) fn new(haystack: &'a Str64) -> Self {
+        let is_whitespace: fn(char) -> bool = char::is_whitespace;
+        Self {
+            inner: SplitInternal::new(haystack, is_whitespace.into_searcher(), true),
+        }
+    }
+}
+
+impl<'a> Iterator for SplitWhitespace<'a> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        core::iter::from_fn(|| self.inner.next()).find(|word| !word.is_empty())
+    }
+}
+
+/// An iterator over the lines of a string.
+///
+/// Created by [`Str64::lines`].
+pub struct Lines<'a> {
+    inner: SplitInternal<'a, CharSearcher>,
+}
+
+impl<'a> Lines<'a> {
+    pub(crate) fn new(haystack: &'a Str64) -> Self {
+        Self {
+            inner: SplitInternal::new(haystack, '\n'.into_searcher(), false),
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a Str64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next()?;
+        // Only a `\r` directly before a `\n` is part of the line ending
+        if self.inner.finished {
+            Some(line)
+        } else {
+            Some(line.strip_suffix('\r').unwrap_or(line))
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! `serde` support, enabled by the `serde` feature.
+//!
+//! Human-readable formats such as JSON and TOML see a [`String64`] as an ordinary
+//! string. Binary formats get its units as a byte string, each unit serialized
+//! big-endian (the SPECIFICATION §8 network byte order, as
+//! [`to_be_bytes`](Str64::to_be_bytes) produces), so the encoding does not depend on
+//! how the format writes integers.
+//!
+//! Deserialization validates every unit against v1.0, reserved bits included. Strings
+//! that hold invalid units fail to serialize in human-readable formats, since they have
+//! no faithful string form.
+
+use crate::byte_order::ByteOrder;
+use crate::str64::Str64;
+use crate::string64::String64;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+impl Serialize for Str64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let s = self.to_string().map_err(ser::Error::custom)?;
+            serializer.serialize_str(&s)
+        } else {
+            serializer.serialize_bytes(&self.to_be_bytes())
+        }
+    }
+}
+
+impl Serialize for String64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_str64().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for String64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(String64Visitor)
+        } else {
+            deserializer.deserialize_bytes(String64Visitor)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<Str64> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String64::deserialize(deserializer).map(String64::into_boxed_str64)
+    }
+}
+
+/// Accepts either representation, whichever the format hands over.
+struct String64Visitor;
+
+impl<'de> Visitor<'de> for String64Visitor {
+    type Value = String64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string or big-endian UTF64 bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(String64::from(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        String64::from_bytes(v, ByteOrder::BigEndian).map_err(E::custom)
+    }
+
+    /// Formats without a native byte string type send bytes as a sequence.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
@@ -0,0 +1,672 @@
+//! Bulk transcoding between UTF-8 and UTF64 (SPECIFICATION §11.1 Level 3).
+//!
+//! A UTF64 unit is the UTF-8 sequence of its character, left-aligned, so neither
+//! direction needs to go through code points: encoding packs each sequence into the top
+//! of a unit as it stands, and decoding copies it back out once the unit is validated.
+//! ASCII runs, the common case, are widened or narrowed a block at a time by the best
+//! kernel the CPU supports, picked at runtime with a portable fallback.
+
+use crate::error::{Result, Utf64Error};
+#[cfg(feature = "alloc")]
+use crate::unit::utf8_sequence_len;
+use crate::unit::validate_unit;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::mem::MaybeUninit;
+
+/// Widens the leading all-ASCII blocks of `bytes` into units, one unit per byte.
+///
+/// Returns the number of bytes consumed, which is also the number of units written.
+/// Stops at the first block holding a non-ASCII byte, or when fewer than a block of
+/// input or output remains.
+#[cfg(feature = "alloc")]
+pub(crate) type WidenAscii = unsafe fn(&[u8], &mut [MaybeUninit<u64>]) -> usize;
+
+/// Counts the characters in the leading whole blocks of `bytes`, which must be valid
+/// UTF-8, by counting the bytes that are not continuation bytes.
+///
+/// Returns the number of bytes examined and the number of characters they start.
+/// Blocks may end partway through a character; its continuation bytes are simply not
+/// counted in the next block either.
+#[cfg(feature = "alloc")]
+pub(crate) type CountChars = unsafe fn(&[u8]) -> (usize, usize);
+
+/// Narrows the leading blocks of `units` that encode only ASCII characters, one byte per
+/// unit, validating them on the way.
+///
+/// Returns the number of units consumed. Stops at the first block holding a unit that
+/// is not a valid ASCII unit, or when fewer than a block of input remains. With `WRITE`
+/// set, also stops when fewer than a block of output remains; without it, `out` is
+/// ignored and the kernel only validates.
+pub(crate) type NarrowAscii = unsafe fn(&[u64], &mut [MaybeUninit<u8>]) -> usize;
+
+/// Bits that are clear in every valid unit encoding an ASCII character: the high bit
+/// of the lead octet and everything below it.
+const NON_ASCII_MASK: u64 = 0x80FF_FFFF_FFFF_FFFF;
+
+/// Bytes or units handled one at a time after a block the kernels rejected.
+const SCALAR_RUN: usize = 16;
+
+/// Appends the UTF64 encoding of `s` to `units`.
+///
+/// The characters are counted up front with a vectorized scan for non-continuation
+/// bytes, so `units` grows at most once and never reserves more than it needs.
+#[cfg(feature = "alloc")]
+pub(crate) fn encode_utf8(s: &str, units: &mut Vec<u64>) {
+    let bytes = s.as_bytes();
+    let count = count_chars(bytes);
+    // A fresh buffer gets exactly what it needs; a growing one keeps amortized growth
+    if units.capacity() == 0 {
+        units.reserve_exact(count);
+    } else {
+        units.reserve(count);
+    }
+
+    let widen = widen_ascii_kernel();
+    let out = &mut units.spare_capacity_mut()[..count];
+    let (mut read, mut written) = (0, 0);
+
+    while read < bytes.len() {
+        if bytes[read].is_ascii() {
+            // SAFETY: the kernel was selected for features this CPU supports, and each
+            // byte it widens is an ASCII character, so it stays within `out`.
+            let n = unsafe { widen(&bytes[read..], &mut out[written..]) };
+            let n = n + widen_ascii_swar(&bytes[read + n..], &mut out[written + n..]);
+            read += n;
+            written += n;
+            if n != 0 {
+                continue;
+            }
+        }
+
+        // The kernels gave up on this block, so take it a character at a time before
+        // trying them again
+        let end = (read + SCALAR_RUN).min(bytes.len());
+        while read < end {
+            let len = utf8_sequence_len(bytes[read]);
+            out[written].write(pack_sequence(&bytes[read..read + len]));
+            read += len;
+            written += 1;
+        }
+    }
+
+    debug_assert_eq!(written, count);
+    // SAFETY: the first `count` spare slots were all initialized above.
+    unsafe { units.set_len(units.len() + count) };
+}
+
+/// Counts the characters in `bytes`, which must be valid UTF-8.
+#[cfg(feature = "alloc")]
+pub(crate) fn count_chars(bytes: &[u8]) -> usize {
+    // SAFETY: the kernel was selected for features this CPU supports.
+    let (read, count) = unsafe { count_chars_kernel()(bytes) };
+    let (swar_read, swar_count) = count_chars_swar(&bytes[read..]);
+    let rest = &bytes[read + swar_read..];
+    count + swar_count + rest.iter().filter(|&&byte| !is_continuation(byte)).count()
+}
+
+/// Returns `true` for a UTF-8 continuation byte, `10xxxxxx`.
+#[cfg(feature = "alloc")]
+fn is_continuation(byte: u8) -> bool {
+    (byte as i8) < -0x40
+}
+
+/// Decodes `units` into UTF-8, validating every unit in the same pass.
+///
+/// The output size is computed up front, so the string is allocated exactly once.
+#[cfg(feature = "alloc")]
+pub(crate) fn decode_utf8(units: &[u64]) -> Result<String> {
+    let size = units.iter().map(|&unit| sequence_len(unit)).sum();
+    let mut bytes = Vec::with_capacity(size);
+
+    let narrow = narrow_ascii_kernel::<true>();
+    let out = &mut bytes.spare_capacity_mut()[..size];
+    let (mut read, mut written) = (0, 0);
+
+    while read < units.len() {
+        // SAFETY: the kernel was selected for features this CPU supports.
+        let n = unsafe { narrow(&units[read..], &mut out[written..]) };
+        read += n;
+        written += n;
+
+        let end = (read + SCALAR_RUN).min(units.len());
+        while read < end {
+            let unit = units[read];
+            let len = if is_ascii_unit(unit) {
+                1
+            } else {
+                let ch = validate_unit(unit).map_err(|kind| Utf64Error::new(kind, read, unit))?;
+                ch.len_utf8()
+            };
+            for (slot, &byte) in out[written..written + len]
+                .iter_mut()
+                .zip(&unit.to_be_bytes())
+            {
+                slot.write(byte);
+            }
+            read += 1;
+            written += len;
+        }
+    }
+
+    // Every unit was valid, so each one took exactly its computed size
+    debug_assert_eq!(written, size);
+    // SAFETY: the first `written` bytes were initialized above.
+    unsafe { bytes.set_len(written) };
+    // SAFETY: every unit passed validation, and a valid unit holds exactly one
+    // well-formed UTF-8 sequence, which is what was copied out.
+    Ok(unsafe { String::from_utf8_unchecked(bytes) })
+}
+
+/// Validates every unit in `units`, reporting the first invalid one.
+pub(crate) fn validate(units: &[u64]) -> Result<()> {
+    let narrow = narrow_ascii_kernel::<false>();
+    let mut read = 0;
+
+    while read < units.len() {
+        // SAFETY: the kernel was selected for features this CPU supports, and does not
+        // touch `out` when not writing.
+        read += unsafe { narrow(&units[read..], &mut []) };
+
+        let end = (read + SCALAR_RUN).min(units.len());
+        while read < end {
+            let unit = units[read];
+            if !is_ascii_unit(unit) {
+                validate_unit(unit).map_err(|kind| Utf64Error::new(kind, read, unit))?;
+            }
+            read += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if `unit` is a valid unit encoding an ASCII character, U+0000
+/// included.
+///
+/// A cheap check that lets mostly-ASCII text skip the full validator.
+fn is_ascii_unit(unit: u64) -> bool {
+    unit & NON_ASCII_MASK == 0
+}
+
+/// Returns the length of the UTF-8 sequence a unit holds, assuming it is valid.
+///
+/// Counts the octets of the upper half up to its last non-zero one; continuation
+/// octets are never zero, so for a valid unit that is exactly the sequence. The lead
+/// octet always counts, which covers U+0000.
+#[cfg(feature = "alloc")]
+fn sequence_len(unit: u64) -> usize {
+    let upper = (unit >> 32) as u32;
+    4 - (upper.trailing_zeros().min(24) as usize / 8)
+}
+
+/// Packs one UTF-8 sequence into the upper half of a unit.
+#[cfg(feature = "alloc")]
+fn pack_sequence(sequence: &[u8]) -> u64 {
+    let mut unit = 0;
+    for (i, &byte) in sequence.iter().enumerate() {
+        unit |= (byte as u64) << (56 - i * 8);
+    }
+    unit
+}
+
+/// Checks for a CPU feature at runtime, or at compile time without `std`, where runtime
+/// detection is unavailable.
+#[cfg(any(
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_endian = "little")
+))]
+macro_rules! detected {
+    ($detect:ident, $feature:tt) => {{
+        #[cfg(feature = "std")]
+        let detected = std::arch::$detect!($feature);
+        #[cfg(not(feature = "std"))]
+        let detected = cfg!(target_feature = $feature);
+        detected
+    }};
+}
+
+/// Picks the fastest ASCII widening kernel this CPU supports.
+#[cfg(feature = "alloc")]
+fn widen_ascii_kernel() -> WidenAscii {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if detected!(is_x86_feature_detected, "avx2") {
+            return x86_64::widen_ascii_avx2;
+        }
+        if detected!(is_x86_feature_detected, "sse2") {
+            return x86_64::widen_ascii_sse2;
+        }
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    {
+        if detected!(is_aarch64_feature_detected, "neon") {
+            return aarch64::widen_ascii_neon;
+        }
+    }
+
+    widen_ascii_swar
+}
+
+/// Picks the fastest character counting kernel this CPU supports.
+#[cfg(feature = "alloc")]
+fn count_chars_kernel() -> CountChars {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if detected!(is_x86_feature_detected, "avx2") {
+            return x86_64::count_chars_avx2;
+        }
+        if detected!(is_x86_feature_detected, "sse2") {
+            return x86_64::count_chars_sse2;
+        }
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    {
+        if detected!(is_aarch64_feature_detected, "neon") {
+            return aarch64::count_chars_neon;
+        }
+    }
+
+    count_chars_swar
+}
+
+/// Picks the fastest ASCII narrowing kernel this CPU supports.
+fn narrow_ascii_kernel<const WRITE: bool>() -> NarrowAscii {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if detected!(is_x86_feature_detected, "sse2") {
+            return x86_64::narrow_ascii_sse2::<WRITE>;
+        }
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    {
+        if detected!(is_aarch64_feature_detected, "neon") {
+            return aarch64::narrow_ascii_neon::<WRITE>;
+        }
+    }
+
+    narrow_ascii_scalar::<WRITE>
+}
+
+/// Portable kernel: checks and narrows one unit at a time.
+pub(crate) fn narrow_ascii_scalar<const WRITE: bool>(
+    units: &[u64],
+    out: &mut [MaybeUninit<u8>],
+) -> usize {
+    let len = if WRITE {
+        units.len().min(out.len())
+    } else {
+        units.len()
+    };
+
+    for (i, &unit) in units[..len].iter().enumerate() {
+        if !is_ascii_unit(unit) {
+            return i;
+        }
+        if WRITE {
+            out[i].write((unit >> 56) as u8);
+        }
+    }
+    len
+}
+
+/// Portable kernel: checks eight bytes at a time with word arithmetic.
+#[cfg(feature = "alloc")]
+pub(crate) fn widen_ascii_swar(bytes: &[u8], out: &mut [MaybeUninit<u64>]) -> usize {
+    const BLOCK: usize = 8;
+    let blocks = bytes.len().min(out.len()) / BLOCK;
+
+    let mut done = 0;
+    for (input, output) in bytes
+        .chunks_exact(BLOCK)
+        .zip(out.chunks_exact_mut(BLOCK))
+        .take(blocks)
+    {
+        let word = u64::from_ne_bytes(input.try_into().expect("block of 8 bytes"));
+        if word & 0x8080_8080_8080_8080 != 0 {
+            break;
+        }
+        for (slot, &byte) in output.iter_mut().zip(input) {
+            slot.write((byte as u64) << 56);
+        }
+        done += BLOCK;
+    }
+    done
+}
+
+/// Portable kernel: finds the continuation bytes of eight bytes at a time with word
+/// arithmetic.
+#[cfg(feature = "alloc")]
+pub(crate) fn count_chars_swar(bytes: &[u8]) -> (usize, usize) {
+    const BLOCK: usize = 8;
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    let mut count = 0;
+    let blocks = bytes.chunks_exact(BLOCK);
+    let read = bytes.len() - blocks.remainder().len();
+    for block in blocks {
+        let word = u64::from_ne_bytes(block.try_into().expect("block of 8 bytes"));
+        // A continuation byte has its top bit set and the one below it clear; shifting
+        // left moves each byte's bit 6 under its bit 7 without crossing into the next
+        let continuations = word & !(word << 1) & HIGH_BITS;
+        count += BLOCK - continuations.count_ones() as usize;
+    }
+    (read, count)
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod x86_64 {
+    use core::arch::x86_64::*;
+    use core::mem::MaybeUninit;
+
+    /// Widens 16 bytes per step by interleaving them with zeros three times, which
+    /// moves each byte to the top of its own 64-bit lane.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support SSE2.
+    #[cfg(feature = "alloc")]
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn widen_ascii_sse2(bytes: &[u8], out: &mut [MaybeUninit<u64>]) -> usize {
+        const BLOCK: usize = 16;
+        let blocks = bytes.len().min(out.len()) / BLOCK;
+        let zero = _mm_setzero_si128();
+
+        for block in 0..blocks {
+            let offset = block * BLOCK;
+            // SAFETY: `blocks` keeps every read within `bytes` and every write within
+            // `out`; the intrinsics have no alignment requirement.
+            unsafe {
+                let input = _mm_loadu_si128(bytes.as_ptr().add(offset).cast());
+                if _mm_movemask_epi8(input) != 0 {
+                    return offset;
+                }
+
+                let dst = out.as_mut_ptr().add(offset).cast::<__m128i>();
+                let words = [
+                    _mm_unpacklo_epi8(zero, input),
+                    _mm_unpackhi_epi8(zero, input),
+                ];
+                for (i, words) in words.into_iter().enumerate() {
+                    let dwords = [
+                        _mm_unpacklo_epi16(zero, words),
+                        _mm_unpackhi_epi16(zero, words),
+                    ];
+                    for (j, dwords) in dwords.into_iter().enumerate() {
+                        let k = i * 4 + j * 2;
+                        _mm_storeu_si128(dst.add(k), _mm_unpacklo_epi32(zero, dwords));
+                        _mm_storeu_si128(dst.add(k + 1), _mm_unpackhi_epi32(zero, dwords));
+                    }
+                }
+            }
+        }
+        blocks * BLOCK
+    }
+
+    /// Counts 16 bytes per step: a signed compare picks out the continuation bytes,
+    /// which are exactly those below -0x40.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support SSE2.
+    #[cfg(feature = "alloc")]
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn count_chars_sse2(bytes: &[u8]) -> (usize, usize) {
+        const BLOCK: usize = 16;
+        let blocks = bytes.len() / BLOCK;
+        let threshold = _mm_set1_epi8(-0x40);
+
+        let mut count = 0;
+        for block in 0..blocks {
+            // SAFETY: `blocks` keeps every read within `bytes`; the load has no
+            // alignment requirement.
+            let input = unsafe { _mm_loadu_si128(bytes.as_ptr().add(block * BLOCK).cast()) };
+            let continuations = _mm_movemask_epi8(_mm_cmplt_epi8(input, threshold));
+            count += BLOCK - continuations.count_ones() as usize;
+        }
+        (blocks * BLOCK, count)
+    }
+
+    /// Counts 32 bytes per step, as [`count_chars_sse2`] does.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[cfg(feature = "alloc")]
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn count_chars_avx2(bytes: &[u8]) -> (usize, usize) {
+        const BLOCK: usize = 32;
+        let blocks = bytes.len() / BLOCK;
+        let threshold = _mm256_set1_epi8(-0x40);
+
+        let mut count = 0;
+        for block in 0..blocks {
+            // SAFETY: `blocks` keeps every read within `bytes`; the load has no
+            // alignment requirement.
+            let input = unsafe { _mm256_loadu_si256(bytes.as_ptr().add(block * BLOCK).cast()) };
+            let continuations = _mm256_movemask_epi8(_mm256_cmpgt_epi8(threshold, input));
+            count += BLOCK - continuations.count_ones() as usize;
+        }
+        (blocks * BLOCK, count)
+    }
+
+    /// Narrows 16 units per step: shifts each lead octet to the bottom of its lane, then
+    /// packs the lanes down to bytes with three rounds of saturating packs.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support SSE2.
+    #[target_feature(enable = "sse2")]
+    pub(crate) unsafe fn narrow_ascii_sse2<const WRITE: bool>(
+        units: &[u64],
+        out: &mut [MaybeUninit<u8>],
+    ) -> usize {
+        const BLOCK: usize = 16;
+        let len = if WRITE {
+            units.len().min(out.len())
+        } else {
+            units.len()
+        };
+        let blocks = len / BLOCK;
+        let mask = _mm_set1_epi64x(super::NON_ASCII_MASK as i64);
+        let zero = _mm_setzero_si128();
+
+        for block in 0..blocks {
+            let offset = block * BLOCK;
+            // SAFETY: `blocks` keeps every read within `units` and every write within
+            // `out`; the intrinsics have no alignment requirement.
+            unsafe {
+                let src = units.as_ptr().add(offset).cast::<__m128i>();
+                let mut stray = zero;
+                let mut leads = [zero; BLOCK / 2];
+                for (i, lead) in leads.iter_mut().enumerate() {
+                    let pair = _mm_loadu_si128(src.add(i));
+                    stray = _mm_or_si128(stray, _mm_and_si128(pair, mask));
+                    *lead = _mm_srli_epi64::<56>(pair);
+                }
+
+                let words = [
+                    _mm_packs_epi32(leads[0], leads[1]),
+                    _mm_packs_epi32(leads[2], leads[3]),
+                    _mm_packs_epi32(leads[4], leads[5]),
+                    _mm_packs_epi32(leads[6], leads[7]),
+                ];
+                let bytes = _mm_packus_epi16(
+                    _mm_packs_epi32(words[0], words[1]),
+                    _mm_packs_epi32(words[2], words[3]),
+                );
+
+                // Any stray bit needs the full validator
+                if _mm_movemask_epi8(_mm_cmpeq_epi8(stray, zero)) != 0xFFFF {
+                    return offset;
+                }
+                if WRITE {
+                    _mm_storeu_si128(out.as_mut_ptr().add(offset).cast(), bytes);
+                }
+            }
+        }
+        blocks * BLOCK
+    }
+
+    /// Widens 32 bytes per step, zero-extending four bytes at a time into 64-bit lanes
+    /// and shifting them to the top.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[cfg(feature = "alloc")]
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn widen_ascii_avx2(bytes: &[u8], out: &mut [MaybeUninit<u64>]) -> usize {
+        const BLOCK: usize = 32;
+        let blocks = bytes.len().min(out.len()) / BLOCK;
+
+        for block in 0..blocks {
+            let offset = block * BLOCK;
+            // SAFETY: `blocks` keeps every read within `bytes` and every write within
+            // `out`; the intrinsics have no alignment requirement.
+            unsafe {
+                let src = bytes.as_ptr().add(offset);
+                let input = _mm256_loadu_si256(src.cast());
+                if _mm256_movemask_epi8(input) != 0 {
+                    return offset;
+                }
+
+                let dst = out.as_mut_ptr().add(offset).cast::<__m256i>();
+                for i in 0..BLOCK / 4 {
+                    let four = _mm_cvtsi32_si128(src.add(i * 4).cast::<i32>().read_unaligned());
+                    let units = _mm256_slli_epi64::<56>(_mm256_cvtepu8_epi64(four));
+                    _mm256_storeu_si256(dst.add(i), units);
+                }
+            }
+        }
+        blocks * BLOCK
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+pub(crate) mod aarch64 {
+    use core::arch::aarch64::*;
+    use core::mem::MaybeUninit;
+
+    /// Widens 16 bytes per step through successive lengthening moves, then shifts each
+    /// byte to the top of its 64-bit lane.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support NEON.
+    #[cfg(feature = "alloc")]
+    #[target_feature(enable = "neon")]
+    pub(crate) unsafe fn widen_ascii_neon(bytes: &[u8], out: &mut [MaybeUninit<u64>]) -> usize {
+        const BLOCK: usize = 16;
+        let blocks = bytes.len().min(out.len()) / BLOCK;
+
+        for block in 0..blocks {
+            let offset = block * BLOCK;
+            // SAFETY: `blocks` keeps every read within `bytes` and every write within
+            // `out`; the intrinsics have no alignment requirement.
+            unsafe {
+                let input = vld1q_u8(bytes.as_ptr().add(offset));
+                if vmaxvq_u8(input) >= 0x80 {
+                    return offset;
+                }
+
+                let dst = out.as_mut_ptr().add(offset).cast::<u64>();
+                let halves = [vmovl_u8(vget_low_u8(input)), vmovl_high_u8(input)];
+                for (i, half) in halves.into_iter().enumerate() {
+                    let quarters = [vmovl_u16(vget_low_u16(half)), vmovl_high_u16(half)];
+                    for (j, quarter) in quarters.into_iter().enumerate() {
+                        let k = i * 8 + j * 4;
+                        let low = vshlq_n_u64::<56>(vmovl_u32(vget_low_u32(quarter)));
+                        let high = vshlq_n_u64::<56>(vmovl_high_u32(quarter));
+                        vst1q_u64(dst.add(k), low);
+                        vst1q_u64(dst.add(k + 2), high);
+                    }
+                }
+            }
+        }
+        blocks * BLOCK
+    }
+
+    /// Counts 16 bytes per step: a signed compare picks out the continuation bytes,
+    /// which are exactly those below -0x40, and a horizontal add totals them.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support NEON.
+    #[cfg(feature = "alloc")]
+    #[target_feature(enable = "neon")]
+    pub(crate) unsafe fn count_chars_neon(bytes: &[u8]) -> (usize, usize) {
+        const BLOCK: usize = 16;
+        let blocks = bytes.len() / BLOCK;
+        let threshold = vdupq_n_s8(-0x40);
+
+        let mut count = 0;
+        for block in 0..blocks {
+            // SAFETY: `blocks` keeps every read within `bytes`; the load has no
+            // alignment requirement.
+            let input = unsafe { vld1q_s8(bytes.as_ptr().add(block * BLOCK).cast()) };
+            let continuations = vandq_u8(vcltq_s8(input, threshold), vdupq_n_u8(1));
+            count += BLOCK - vaddvq_u8(continuations) as usize;
+        }
+        (blocks * BLOCK, count)
+    }
+
+    /// Narrows 16 units per step: shifts each lead octet to the bottom of its lane, then
+    /// halves the lane width three times.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support NEON.
+    #[target_feature(enable = "neon")]
+    pub(crate) unsafe fn narrow_ascii_neon<const WRITE: bool>(
+        units: &[u64],
+        out: &mut [MaybeUninit<u8>],
+    ) -> usize {
+        const BLOCK: usize = 16;
+        let len = if WRITE {
+            units.len().min(out.len())
+        } else {
+            units.len()
+        };
+        let blocks = len / BLOCK;
+        let mask = vdupq_n_u64(super::NON_ASCII_MASK);
+
+        for block in 0..blocks {
+            let offset = block * BLOCK;
+            // SAFETY: `blocks` keeps every read within `units` and every write within
+            // `out`; the intrinsics have no alignment requirement.
+            unsafe {
+                let src = units.as_ptr().add(offset);
+                let mut stray = vdupq_n_u64(0);
+                let mut leads = [vdup_n_u32(0); BLOCK / 2];
+                for (i, lead) in leads.iter_mut().enumerate() {
+                    let pair = vld1q_u64(src.add(i * 2));
+                    stray = vorrq_u64(stray, vandq_u64(pair, mask));
+                    *lead = vmovn_u64(vshrq_n_u64::<56>(pair));
+                }
+
+                let halves = [
+                    vmovn_u32(vcombine_u32(leads[0], leads[1])),
+                    vmovn_u32(vcombine_u32(leads[2], leads[3])),
+                    vmovn_u32(vcombine_u32(leads[4], leads[5])),
+                    vmovn_u32(vcombine_u32(leads[6], leads[7])),
+                ];
+                let bytes = vcombine_u8(
+                    vmovn_u16(vcombine_u16(halves[0], halves[1])),
+                    vmovn_u16(vcombine_u16(halves[2], halves[3])),
+                );
+
+                // Any stray bit needs the full validator
+                if vmaxvq_u32(vreinterpretq_u32_u64(stray)) != 0 {
+                    return offset;
+                }
+                if WRITE {
+                    vst1q_u8(out.as_mut_ptr().add(offset).cast(), bytes);
+                }
+            }
+        }
+        blocks * BLOCK
+    }
+}
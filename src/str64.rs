@@ -0,0 +1,1255 @@
+#[cfg(feature = "alloc")]
+use crate::byte_order::{BOM, ByteOrder, write_units};
+use crate::char64::Char64;
+use crate::error::{ErrorKind, Result, Utf64Error};
+use crate::pattern::{
+    Lines, MatchIndices, Matches, Pattern64, RSplit, Searcher64, Split, SplitN, SplitTerminator,
+    SplitWhitespace,
+};
+#[cfg(feature = "alloc")]
+use crate::simd::decode_utf8;
+#[cfg(feature = "alloc")]
+use crate::string64::String64;
+use crate::unit::{decode_lossy, encode_char, map_ascii, validate_unit, validate_units};
+#[cfg(feature = "alloc")]
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Write},
+    iter::FusedIterator,
+    ops::{Index, Range, RangeFrom, RangeFull, RangeTo},
+    slice,
+};
+
+/// A borrowed UTF64 string slice.
+///
+/// `Str64` is to [`String64`] what `str` is to `String`: an unsized view over a run of
+/// UTF64 units. [`String64`] dereferences to it, and range indexing returns `&Str64`,
+/// so substrings keep their string semantics. It has the same layout as `[u64]`.
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::{Str64, String64};
+///
+/// let s = String64::from("Hello, 世界!");
+/// let world: &Str64 = &s[7..9];
+/// assert_eq!(world.len(), 2);
+/// assert_eq!(world, "世界");
+/// assert_eq!(world.to_string().unwrap(), "世界");
+/// ```
+#[derive(PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Str64 {
+    units: [u64],
+}
+
+impl Str64 {
+    /// Views a slice of raw UTF64 units as a `Str64`, validating every unit.
+    pub fn from_units(units: &[u64]) -> Result<&Str64> {
+        validate_units(units)?;
+        Ok(Self::from_raw(units))
+    }
+
+    /// Views a slice of raw UTF64 units as a `Str64` without validating them.
+    ///
+    /// Not `unsafe`: a `Str64` may hold invalid units anyway, which the lossy APIs
+    /// decode as U+FFFD and the strict ones report as errors.
+    pub fn from_units_unchecked(units: &[u64]) -> &Str64 {
+        Self::from_raw(units)
+    }
+
+    /// Views bytes holding units in the platform's native byte order as a `Str64`,
+    /// without copying.
+    ///
+    /// This is the way back from [`as_slice`](Self::as_slice) for data that arrives as
+    /// bytes, such as a memory-mapped file. Every unit is validated in place.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::Misaligned`] if `bytes` does not start on an 8-octet
+    /// boundary, with [`ErrorKind::IncompleteUnit`] if its length is not a multiple of 8,
+    /// and otherwise as [`from_units`](Self::from_units) does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::{ErrorKind, Str64, String64};
+    ///
+    /// let s = String64::from("Hi!");
+    /// let units = s.as_slice();
+    /// // SAFETY: any initialized memory is valid as bytes.
+    /// let bytes = unsafe { std::slice::from_raw_parts(units.as_ptr().cast::<u8>(), 24) };
+    ///
+    /// assert_eq!(Str64::from_bytes_ne(bytes).unwrap(), "Hi!");
+    /// let err = Str64::from_bytes_ne(&bytes[1..9]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Misaligned);
+    /// ```
+    pub fn from_bytes_ne(bytes: &[u8]) -> Result<&Str64> {
+        // An empty slice may dangle anywhere, but views no units
+        if bytes.is_empty() {
+            return Ok(Self::from_raw(&[]));
+        }
+        if !(bytes.as_ptr() as usize).is_multiple_of(align_of::<u64>()) {
+            return Err(Utf64Error::new(ErrorKind::Misaligned, 0, 0));
+        }
+        let remainder = bytes.len() % 8;
+        if remainder != 0 {
+            return Err(Utf64Error::new(
+                ErrorKind::IncompleteUnit {
+                    len: remainder as u8,
+                },
+                bytes.len() / 8,
+                0,
+            ));
+        }
+
+        // SAFETY: the pointer is aligned for `u64` and covers `bytes.len() / 8` whole
+        // units of initialized memory, and every bit pattern is a valid `u64`.
+        let units = unsafe { slice::from_raw_parts(bytes.as_ptr().cast::<u64>(), bytes.len() / 8) };
+        Self::from_units(units)
+    }
+
+    /// Views raw units as a `Str64` regardless of their validity.
+    pub(crate) fn from_raw(units: &[u64]) -> &Str64 {
+        // SAFETY: `Str64` is `repr(transparent)` over `[u64]`.
+        unsafe { &*(units as *const [u64] as *const Str64) }
+    }
+
+    /// Mutable counterpart of [`from_raw`](Self::from_raw).
+    pub(crate) fn from_raw_mut(units: &mut [u64]) -> &mut Str64 {
+        // SAFETY: `Str64` is `repr(transparent)` over `[u64]`.
+        unsafe { &mut *(units as *mut [u64] as *mut Str64) }
+    }
+
+    /// Returns the length of this `Str64` in characters.
+    ///
+    /// Note: This is O(1) as each character is exactly one u64.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns `true` if this `Str64` has a length of zero.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Returns a slice of the underlying u64 data.
+    pub fn as_slice(&self) -> &[u64] {
+        &self.units
+    }
+
+    /// Returns a mutable slice of the underlying u64 data.
+    ///
+    /// Units written through the slice are not validated. Like any invalid unit, one
+    /// left invalid reads as U+FFFD REPLACEMENT CHARACTER and is reported by the strict
+    /// APIs such as [`to_string`](Self::to_string).
+    pub fn as_slice_mut(&mut self) -> &mut [u64] {
+        &mut self.units
+    }
+
+    /// Returns an iterator over the characters of this `Str64`.
+    ///
+    /// Units that fail validation are yielded as U+FFFD REPLACEMENT CHARACTER, so
+    /// iteration never panics. Use [`try_chars`](Self::try_chars) to observe the errors.
+    pub fn chars_lossy(&self) -> Iter<'_> {
+        Iter {
+            data: self.units.iter(),
+        }
+    }
+
+    /// Returns an iterator over the characters of this `Str64` and their indices.
+    ///
+    /// Indices count characters, so they can be used directly to index the string.
+    /// Invalid units are yielded as U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("añb");
+    /// let pairs: Vec<_> = s.char_indices().rev().collect();
+    /// assert_eq!(pairs, [(2, 'b'), (1, 'ñ'), (0, 'a')]);
+    /// ```
+    pub fn char_indices(&self) -> CharIndices<'_> {
+        CharIndices {
+            data: self.units.iter().enumerate(),
+        }
+    }
+
+    /// Returns an iterator over the string encoded as UTF-16.
+    ///
+    /// Characters outside the Basic Multilingual Plane become surrogate pairs. Units
+    /// that fail validation are encoded as U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("Hi🌍");
+    /// let utf16: Vec<u16> = s.encode_utf16().collect();
+    /// assert_eq!(utf16, [0x0048, 0x0069, 0xD83C, 0xDF0D]);
+    /// assert_eq!(String64::from_utf16(&utf16).unwrap(), s);
+    /// ```
+    pub fn encode_utf16(&self) -> EncodeUtf16<'_> {
+        EncodeUtf16 {
+            data: self.units.iter(),
+            low_surrogate: None,
+        }
+    }
+
+    /// Returns an iterator over the units of this `Str64` as [`Char64`] values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("a€");
+    /// let lens: Vec<usize> = s.units().map(|c| c.utf8_len()).collect();
+    /// assert_eq!(lens, [1, 3]);
+    /// ```
+    pub fn units(&self) -> Units<'_> {
+        Units {
+            data: self.units.iter(),
+        }
+    }
+
+    /// Returns an iterator that decodes each unit, yielding an error for invalid ones.
+    ///
+    /// Each error carries the index of the unit that produced it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::BorrowMut;
+    /// use utf_64::String64;
+    ///
+    /// let mut s = String64::from("abc");
+    /// let units: &mut [u64] = s.borrow_mut();
+    /// units[1] = 0xFF;
+    ///
+    /// let results: Vec<_> = s.try_chars().collect();
+    /// assert_eq!(results[0], Ok('a'));
+    /// assert_eq!(results[1].unwrap_err().index(), 1);
+    /// assert_eq!(results[2], Ok('c'));
+    /// ```
+    pub fn try_chars(&self) -> TryChars<'_> {
+        TryChars {
+            data: self.units.iter().enumerate(),
+        }
+    }
+
+    /// Decodes this UTF64 string back to a standard Rust String.
+    ///
+    /// Every unit is fully validated in the same pass that copies it out, and the
+    /// output is allocated once at its exact size. The first unit that violates the v1.0
+    /// specification determines the returned error.
+    ///
+    /// The owned string types that dereference to `Str64` repeat this method, so that
+    /// it takes precedence over the [`ToString::to_string`] their `Display` impls
+    /// provide.
+    #[cfg(feature = "alloc")]
+    pub fn to_string(&self) -> Result<String> {
+        decode_utf8(&self.units)
+    }
+
+    /// Decodes this UTF64 string, replacing invalid units with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Any unit that fails reserved-bit, padding or UTF-8 validation becomes a single
+    /// replacement character, so the result always has [`len`](Self::len) characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::BorrowMut;
+    /// use utf_64::String64;
+    ///
+    /// let mut s = String64::from("a?c");
+    /// let units: &mut [u64] = s.borrow_mut();
+    /// units[1] |= 1;
+    ///
+    /// assert_eq!(s.to_string_lossy(), "a\u{FFFD}c");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        Cow::Owned(self.chars_lossy().collect())
+    }
+
+    /// Serializes the units of this string in the given byte order.
+    ///
+    /// Each unit becomes 8 octets; no byte order mark is written.
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes(&self, order: ByteOrder) -> Vec<u8> {
+        write_units(&self.units, order)
+    }
+
+    /// Serializes the units of this string in the given byte order, preceded by a [`BOM`].
+    ///
+    /// This is the form SPECIFICATION §8.2 recommends for interchange, and what
+    /// [`String64::from_bytes_detect`] expects.
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes_with_bom(&self, order: ByteOrder) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity((self.units.len() + 1) * 8);
+        bytes.extend_from_slice(&order.write_unit(BOM));
+        bytes.extend(write_units(&self.units, order));
+        bytes
+    }
+
+    /// Serializes the units of this string in big-endian (network) byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("A");
+    /// assert_eq!(s.to_be_bytes(), [0x41, 0, 0, 0, 0, 0, 0, 0]);
+    /// assert_eq!(s.to_le_bytes(), [0, 0, 0, 0, 0, 0, 0, 0x41]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.to_bytes(ByteOrder::BigEndian)
+    }
+
+    /// Serializes the units of this string in little-endian byte order.
+    #[cfg(feature = "alloc")]
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.to_bytes(ByteOrder::LittleEndian)
+    }
+
+    /// Serializes the units of this string in the platform's native byte order.
+    #[cfg(feature = "alloc")]
+    pub fn to_ne_bytes(&self) -> Vec<u8> {
+        self.to_bytes(ByteOrder::NATIVE)
+    }
+
+    /// Copies this `Str64` into a new `String64`.
+    #[cfg(feature = "alloc")]
+    pub fn to_string64(&self) -> String64 {
+        self.to_owned()
+    }
+
+    /// Returns the character index of the first match of `pat`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("日本語のテキスト");
+    /// assert_eq!(s.find('の'), Some(3));
+    /// assert_eq!(s.find("テキ"), Some(4));
+    /// assert_eq!(s.find('x'), None);
+    /// ```
+    pub fn find<P: Pattern64>(&self, pat: P) -> Option<usize> {
+        pat.into_searcher()
+            .find_in(&self.units)
+            .map(|(start, _)| start)
+    }
+
+    /// Returns the character index of the last match of `pat`.
+    pub fn rfind<P: Pattern64>(&self, pat: P) -> Option<usize> {
+        pat.into_searcher()
+            .rfind_in(&self.units)
+            .map(|(start, _)| start)
+    }
+
+    /// Returns `true` if `pat` matches anywhere in this string.
+    pub fn contains<P: Pattern64>(&self, pat: P) -> bool {
+        self.find(pat).is_some()
+    }
+
+    /// Returns `true` if `pat` matches at the start of this string.
+    pub fn starts_with<P: Pattern64>(&self, pat: P) -> bool {
+        pat.into_searcher().prefix_len(&self.units).is_some()
+    }
+
+    /// Returns `true` if `pat` matches at the end of this string.
+    pub fn ends_with<P: Pattern64>(&self, pat: P) -> bool {
+        pat.into_searcher().suffix_len(&self.units).is_some()
+    }
+
+    /// Returns an iterator over the non-overlapping matches of `pat`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("abc1def2ghi3");
+    /// let digits: Vec<String> = s
+    ///     .matches(char::is_numeric)
+    ///     .map(|m| m.to_string().unwrap())
+    ///     .collect();
+    /// assert_eq!(digits, ["1", "2", "3"]);
+    /// ```
+    pub fn matches<P: Pattern64>(&self, pat: P) -> Matches<'_, P::Searcher> {
+        Matches::new(self, pat.into_searcher())
+    }
+
+    /// Returns an iterator over the non-overlapping matches of `pat` and their
+    /// character indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("añoaño");
+    /// let starts: Vec<usize> = s.match_indices("ño").map(|(i, _)| i).collect();
+    /// assert_eq!(starts, [1, 4]);
+    /// ```
+    pub fn match_indices<P: Pattern64>(&self, pat: P) -> MatchIndices<'_, P::Searcher> {
+        MatchIndices::new(self, pat.into_searcher())
+    }
+
+    /// Returns this string with a leading match of `pat` removed, or `None` if it does
+    /// not start with one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("foo:bar");
+    /// assert_eq!(s.strip_prefix("foo:").unwrap(), "bar");
+    /// assert!(s.strip_prefix("bar").is_none());
+    /// ```
+    pub fn strip_prefix<P: Pattern64>(&self, pat: P) -> Option<&Str64> {
+        let len = pat.into_searcher().prefix_len(&self.units)?;
+        Some(&self[len..])
+    }
+
+    /// Returns this string with a trailing match of `pat` removed, or `None` if it does
+    /// not end with one.
+    pub fn strip_suffix<P: Pattern64>(&self, pat: P) -> Option<&Str64> {
+        let len = pat.into_searcher().suffix_len(&self.units)?;
+        Some(&self[..self.len() - len])
+    }
+
+    /// Returns an iterator over the substrings separated by matches of `pat`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("a,b,,c");
+    /// let parts: Vec<String> = s.split(',').map(|p| p.to_string().unwrap()).collect();
+    /// assert_eq!(parts, ["a", "b", "", "c"]);
+    /// ```
+    pub fn split<P: Pattern64>(&self, pat: P) -> Split<'_, P::Searcher> {
+        Split::new(self, pat.into_searcher())
+    }
+
+    /// Returns an iterator over the substrings separated by matches of `pat`, starting
+    /// from the end of the string.
+    pub fn rsplit<P: Pattern64>(&self, pat: P) -> RSplit<'_, P::Searcher> {
+        RSplit::new(self, pat.into_searcher())
+    }
+
+    /// Returns an iterator over at most `n` substrings separated by matches of `pat`.
+    ///
+    /// The last substring holds the rest of the string, separators included.
+    pub fn splitn<P: Pattern64>(&self, n: usize, pat: P) -> SplitN<'_, P::Searcher> {
+        SplitN::new(self, pat.into_searcher(), n)
+    }
+
+    /// Like [`split`](Self::split), but a trailing empty substring is skipped, so `pat`
+    /// acts as a terminator rather than a separator.
+    pub fn split_terminator<P: Pattern64>(&self, pat: P) -> SplitTerminator<'_, P::Searcher> {
+        SplitTerminator::new(self, pat.into_searcher())
+    }
+
+    /// Returns an iterator over the non-empty substrings separated by whitespace.
+    pub fn split_whitespace(&self) -> SplitWhitespace<'_> {
+        SplitWhitespace::new(self)
+    }
+
+    /// Returns an iterator over the lines of this string.
+    ///
+    /// Lines end with `\n` or `\r\n`, which are not included in the lines. As with
+    /// [`str::lines`], a final line ending is optional and a lone `\r` is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("one\r\ntwo\n\nthree\n");
+    /// let lines: Vec<String> = s.lines().map(|l| l.to_string().unwrap()).collect();
+    /// assert_eq!(lines, ["one", "two", "", "three"]);
+    /// ```
+    pub fn lines(&self) -> Lines<'_> {
+        Lines::new(self)
+    }
+
+    /// Splits this string at the first match of `pat`, returning the substrings before
+    /// and after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("key=value=more");
+    /// let (key, value) = s.split_once('=').unwrap();
+    /// assert_eq!(key, "key");
+    /// assert_eq!(value, "value=more");
+    /// ```
+    pub fn split_once<P: Pattern64>(&self, pat: P) -> Option<(&Str64, &Str64)> {
+        let (start, len) = pat.into_searcher().find_in(&self.units)?;
+        Some((&self[..start], &self[start + len..]))
+    }
+
+    /// Returns the uppercase equivalent of this string as a new `String64`.
+    ///
+    /// Uses the full Unicode case mapping of [`char::to_uppercase`], so the result may
+    /// be longer than the input. Invalid units become U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("straße");
+    /// assert_eq!(s.to_uppercase(), "STRASSE");
+    /// assert_eq!(s.len(), 6);
+    /// assert_eq!(s.to_uppercase().len(), 7);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_uppercase(&self) -> String64 {
+        self.chars_lossy().flat_map(char::to_uppercase).collect()
+    }
+
+    /// Returns the lowercase equivalent of this string as a new `String64`.
+    ///
+    /// Gives the same result as [`str::to_lowercase`], so the result may be longer than
+    /// the input, and a capital sigma at the end of a word becomes `ς` rather than `σ`.
+    /// Invalid units become U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// assert_eq!(String64::from("ΟΔΟΣ ΣΑΣ").to_lowercase(), "οδος σας");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_lowercase(&self) -> String64 {
+        // Only a capital sigma depends on its neighbours, and the Final_Sigma rule looks
+        // past case-ignorable characters but never past whitespace, which is neither
+        // cased nor case-ignorable. So each whitespace-delimited run that holds one is
+        // lowered as a whole by `str::to_lowercase`, and everything else per character.
+        let mut lower = String64::with_capacity(self.len());
+        let mut run = String::new();
+        let mut has_sigma = false;
+        for ch in self.chars_lossy().chain(Some(' ')) {
+            if !ch.is_whitespace() {
+                has_sigma |= ch == 'Σ';
+                run.push(ch);
+                continue;
+            }
+            if has_sigma {
+                lower.push_str(&run.to_lowercase());
+            } else {
+                lower.extend(run.chars().flat_map(char::to_lowercase));
+            }
+            run.clear();
+            has_sigma = false;
+            lower.push(ch);
+        }
+        // Drop the space that flushed the last run
+        lower.pop();
+        lower
+    }
+
+    /// Returns a copy of this string with ASCII letters mapped to uppercase.
+    ///
+    /// Other characters, and invalid units, are copied unchanged.
+    #[cfg(feature = "alloc")]
+    pub fn to_ascii_uppercase(&self) -> String64 {
+        let mut s = self.to_string64();
+        s.make_ascii_uppercase();
+        s
+    }
+
+    /// Returns a copy of this string with ASCII letters mapped to lowercase.
+    ///
+    /// Other characters, and invalid units, are copied unchanged.
+    #[cfg(feature = "alloc")]
+    pub fn to_ascii_lowercase(&self) -> String64 {
+        let mut s = self.to_string64();
+        s.make_ascii_lowercase();
+        s
+    }
+
+    /// Converts ASCII letters in this string to uppercase in place.
+    ///
+    /// Each unit is rewritten where it stands; nothing is reallocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let mut s = String64::from("Grüße, Jürgen");
+    /// s.make_ascii_uppercase();
+    /// assert_eq!(s, "GRüßE, JüRGEN");
+    /// ```
+    pub fn make_ascii_uppercase(&mut self) {
+        for unit in &mut self.units {
+            *unit = map_ascii(*unit, |b| b.to_ascii_uppercase());
+        }
+    }
+
+    /// Converts ASCII letters in this string to lowercase in place.
+    ///
+    /// Each unit is rewritten where it stands; nothing is reallocated.
+    pub fn make_ascii_lowercase(&mut self) {
+        for unit in &mut self.units {
+            *unit = map_ascii(*unit, |b| b.to_ascii_lowercase());
+        }
+    }
+
+    /// Returns `true` if both strings are equal when ASCII letters are compared
+    /// case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let a = String64::from("Ferris");
+    /// assert!(a.eq_ignore_ascii_case(&String64::from("FERRIS")));
+    /// assert!(!a.eq_ignore_ascii_case(&String64::from("FERRİS")));
+    /// ```
+    pub fn eq_ignore_ascii_case(&self, other: &Str64) -> bool {
+        let fold = |unit| map_ascii(unit, |b| b.to_ascii_lowercase());
+        self.len() == other.len()
+            && self
+                .units
+                .iter()
+                .zip(&other.units)
+                .all(|(&a, &b)| fold(a) == fold(b))
+    }
+
+    /// Returns this string with leading and trailing whitespace removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("\u{3000} hello\tworld \n");
+    /// assert_eq!(s.trim(), "hello\tworld");
+    /// assert_eq!(s.trim_start(), "hello\tworld \n");
+    /// ```
+    pub fn trim(&self) -> &Str64 {
+        self.trim_matches(char::is_whitespace)
+    }
+
+    /// Returns this string with leading whitespace removed.
+    pub fn trim_start(&self) -> &Str64 {
+        self.trim_start_matches(char::is_whitespace)
+    }
+
+    /// Returns this string with trailing whitespace removed.
+    pub fn trim_end(&self) -> &Str64 {
+        self.trim_end_matches(char::is_whitespace)
+    }
+
+    /// Returns this string with all leading and trailing matches of `pat` removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from("xxabcxyx");
+    /// assert_eq!(s.trim_matches('x'), "abcxy");
+    /// assert_eq!(s.trim_matches(&['x', 'y']), "abc");
+    /// assert_eq!(s.trim_matches("xx"), "abcxyx");
+    /// ```
+    pub fn trim_matches<P: Pattern64>(&self, pat: P) -> &Str64 {
+        let mut searcher = pat.into_searcher();
+        let start = trimmed_prefix_len(&mut searcher, &self.units);
+        let end = self.len() - trimmed_suffix_len(&mut searcher, &self.units[start..]);
+        &self[start..end]
+    }
+
+    /// Returns this string with all leading matches of `pat` removed.
+    pub fn trim_start_matches<P: Pattern64>(&self, pat: P) -> &Str64 {
+        let start = trimmed_prefix_len(&mut pat.into_searcher(), &self.units);
+        &self[start..]
+    }
+
+    /// Returns this string with all trailing matches of `pat` removed.
+    pub fn trim_end_matches<P: Pattern64>(&self, pat: P) -> &Str64 {
+        let len = trimmed_suffix_len(&mut pat.into_searcher(), &self.units);
+        &self[..self.len() - len]
+    }
+
+    /// Writes the `Debug` representation of this string under the given type name.
+    ///
+    /// Characters are escaped the way `str`'s `Debug` escapes them; invalid units are
+    /// written inline as `\u{?0x...}` with their raw value.
+    pub(crate) fn fmt_debug(&self, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{name}(\"")?;
+        for &unit in &self.units {
+            match validate_unit(unit) {
+                // `str` leaves single quotes unescaped inside double quotes
+                Ok('\'') => f.write_char('\'')?,
+                Ok(ch) => {
+                    for escaped in ch.escape_debug() {
+                        f.write_char(escaped)?;
+                    }
+                }
+                Err(_) => write!(f, "\\u{{?{unit:#018X}}}")?,
+            }
+        }
+        write!(f, "\")")
+    }
+}
+
+/// Returns the number of units covered by consecutive matches at the start of `units`.
+fn trimmed_prefix_len<S: Searcher64>(searcher: &mut S, units: &[u64]) -> usize {
+    let mut start = 0;
+    // An empty match would never advance
+    while let Some(len @ 1..) = searcher.prefix_len(&units[start..]) {
+        start += len;
+    }
+    start
+}
+
+/// Returns the number of units covered by consecutive matches at the end of `units`.
+fn trimmed_suffix_len<S: Searcher64>(searcher: &mut S, units: &[u64]) -> usize {
+    let mut end = units.len();
+    while let Some(len @ 1..) = searcher.suffix_len(&units[..end]) {
+        end -= len;
+    }
+    units.len() - end
+}
+
+#[cfg(feature = "alloc")]
+impl ToOwned for Str64 {
+    type Owned = String64;
+
+    fn to_owned(&self) -> String64 {
+        String64::from_raw(self.units.to_vec())
+    }
+}
+
+impl Default for &Str64 {
+    fn default() -> Self {
+        Str64::from_raw(&[])
+    }
+}
+
+impl fmt::Display for Str64 {
+    /// Writes the string character by character, without allocating.
+    ///
+    /// Width, fill, alignment and precision are honoured the way `str` honours them.
+    /// Invalid units are written as U+FFFD REPLACEMENT CHARACTER.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad_chars(f, self.chars_lossy())
+    }
+}
+
+/// Writes `chars` honouring width, fill, alignment and precision the way `str` does.
+pub(crate) fn pad_chars<I>(f: &mut fmt::Formatter<'_>, chars: I) -> fmt::Result
+where
+    I: ExactSizeIterator<Item = char>,
+{
+    let len = f
+        .precision()
+        .map_or(chars.len(), |precision| precision.min(chars.len()));
+    let chars = chars.take(len);
+
+    let padding = f.width().map_or(0, |width| width.saturating_sub(len));
+    let (before, after) = match f.align() {
+        None | Some(fmt::Alignment::Left) => (0, padding),
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+    };
+
+    let fill = f.fill();
+    for _ in 0..before {
+        f.write_char(fill)?;
+    }
+    for ch in chars {
+        f.write_char(ch)?;
+    }
+    for _ in 0..after {
+        f.write_char(fill)?;
+    }
+    Ok(())
+}
+
+impl fmt::Debug for Str64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug("Str64", f)
+    }
+}
+
+impl PartialOrd for Str64 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Str64 {
+    /// Orders strings lexicographically by code point, like `str`.
+    ///
+    /// Compares the raw units, which orders valid ones by code point as explained on
+    /// [`Char64`'s `Ord`](Char64#impl-Ord-for-Char64). Invalid units are ordered by
+    /// their raw value, which keeps the order total and consistent with `Eq`.
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.units.cmp(&other.units)
+    }
+}
+
+impl Index<usize> for Str64 {
+    type Output = Char64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        Char64::from_unit_ref(&self.units[index])
+    }
+}
+
+impl Index<Range<usize>> for Str64 {
+    type Output = Str64;
+
+    fn index(&self, range: Range<usize>) -> &Self::Output {
+        Str64::from_raw(&self.units[range])
+    }
+}
+
+impl Index<RangeFrom<usize>> for Str64 {
+    type Output = Str64;
+
+    fn index(&self, range: RangeFrom<usize>) -> &Self::Output {
+        Str64::from_raw(&self.units[range])
+    }
+}
+
+impl Index<RangeTo<usize>> for Str64 {
+    type Output = Str64;
+
+    fn index(&self, range: RangeTo<usize>) -> &Self::Output {
+        Str64::from_raw(&self.units[range])
+    }
+}
+
+impl Index<RangeFull> for Str64 {
+    type Output = Str64;
+
+    fn index(&self, _range: RangeFull) -> &Self::Output {
+        self
+    }
+}
+
+impl<'a> IntoIterator for &'a Str64 {
+    type Item = char;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chars_lossy()
+    }
+}
+
+impl PartialEq<str> for Str64 {
+    /// Compares unit by unit without allocating.
+    ///
+    /// Each `char` has exactly one valid encoding, so a string holding an invalid unit
+    /// never equals a `str`.
+    fn eq(&self, other: &str) -> bool {
+        let mut chars = other.chars();
+        self.units
+            .iter()
+            .all(|&unit| chars.next().map(encode_char) == Some(unit))
+            && chars.next().is_none()
+    }
+}
+
+impl PartialEq<&str> for Str64 {
+    fn eq(&self, other: &&str) -> bool {
+        self.eq(*other)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<String> for Str64 {
+    fn eq(&self, other: &String) -> bool {
+        self.eq(other.as_str())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<String64> for Str64 {
+    fn eq(&self, other: &String64) -> bool {
+        self == &**other
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<String64> for &Str64 {
+    fn eq(&self, other: &String64) -> bool {
+        *self == &**other
+    }
+}
+
+impl AsRef<[u64]> for Str64 {
+    fn as_ref(&self) -> &[u64] {
+        &self.units
+    }
+}
+
+impl AsRef<Str64> for Str64 {
+    fn as_ref(&self) -> &Str64 {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryFrom<&Str64> for String {
+    type Error = Utf64Error;
+
+    fn try_from(value: &Str64) -> Result<Self> {
+        value.to_string()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&Str64> for String64 {
+    fn from(s: &Str64) -> Self {
+        s.to_owned()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&Str64> for Box<Str64> {
+    fn from(s: &Str64) -> Self {
+        s.to_owned().into_boxed_str64()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<String64> for Box<Str64> {
+    fn from(s: String64) -> Self {
+        s.into_boxed_str64()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<Box<Str64>> for String64 {
+    fn from(s: Box<Str64>) -> Self {
+        // SAFETY: `Str64` is `repr(transparent)` over `[u64]`.
+        let units = unsafe { Box::from_raw(Box::into_raw(s) as *mut [u64]) };
+        String64::from_raw(units.into_vec())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Clone for Box<Str64> {
+    fn clone(&self) -> Self {
+        Box::from(&**self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&Str64> for Rc<Str64> {
+    fn from(s: &Str64) -> Self {
+        let units: Rc<[u64]> = Rc::from(&s.units);
+        // SAFETY: `Str64` is `repr(transparent)` over `[u64]`.
+        unsafe { Rc::from_raw(Rc::into_raw(units) as *const Str64) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&Str64> for Arc<Str64> {
+    fn from(s: &Str64) -> Self {
+        let units: Arc<[u64]> = Arc::from(&s.units);
+        // SAFETY: `Str64` is `repr(transparent)` over `[u64]`.
+        unsafe { Arc::from_raw(Arc::into_raw(units) as *const Str64) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<String64> for Rc<Str64> {
+    fn from(s: String64) -> Self {
+        Rc::from(&*s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<String64> for Arc<Str64> {
+    fn from(s: String64) -> Self {
+        Arc::from(&*s)
+    }
+}
+
+/// Iterator that yields characters from a &Str64 without consuming it.
+///
+/// Units that fail validation are yielded as U+FFFD REPLACEMENT CHARACTER.
+pub struct Iter<'a> {
+    data: core::slice::Iter<'a, u64>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().copied().map(decode_lossy)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth(n).copied().map(decode_lossy)
+    }
+
+    fn count(self) -> usize {
+        self.data.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back().copied().map(decode_lossy)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth_back(n).copied().map(decode_lossy)
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl<'a> FusedIterator for Iter<'a> {}
+
+/// Iterator that yields the characters of a &Str64 or the error for each invalid unit.
+///
+/// Created by [`Str64::try_chars`].
+pub struct TryChars<'a> {
+    data: core::iter::Enumerate<core::slice::Iter<'a, u64>>,
+}
+
+impl<'a> TryChars<'a> {
+    fn decode((index, &unit): (usize, &u64)) -> Result<char> {
+        validate_unit(unit).map_err(|kind| Utf64Error::new(kind, index, unit))
+    }
+}
+
+impl<'a> Iterator for TryChars<'a> {
+    type Item = Result<char>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(Self::decode)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth(n).map(Self::decode)
+    }
+
+    fn count(self) -> usize {
+        self.data.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<'a> DoubleEndedIterator for TryChars<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back().map(Self::decode)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth_back(n).map(Self::decode)
+    }
+}
+
+impl<'a> ExactSizeIterator for TryChars<'a> {
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl<'a> FusedIterator for TryChars<'a> {}
+
+/// Iterator over the characters of a &Str64 and their character indices.
+///
+/// Created by [`Str64::char_indices`]. Units that fail validation are yielded as
+/// U+FFFD REPLACEMENT CHARACTER.
+pub struct CharIndices<'a> {
+    data: core::iter::Enumerate<core::slice::Iter<'a, u64>>,
+}
+
+impl<'a> Iterator for CharIndices<'a> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data
+            .next()
+            .map(|(index, &unit)| (index, decode_lossy(unit)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data
+            .nth(n)
+            .map(|(index, &unit)| (index, decode_lossy(unit)))
+    }
+
+    fn count(self) -> usize {
+        self.data.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<'a> DoubleEndedIterator for CharIndices<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data
+            .next_back()
+            .map(|(index, &unit)| (index, decode_lossy(unit)))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data
+            .nth_back(n)
+            .map(|(index, &unit)| (index, decode_lossy(unit)))
+    }
+}
+
+impl<'a> ExactSizeIterator for CharIndices<'a> {
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl<'a> FusedIterator for CharIndices<'a> {}
+
+/// Iterator over the units of a &Str64 as [`Char64`] values.
+///
+/// Created by [`Str64::units`]. Units are yielded as stored, without validation.
+pub struct Units<'a> {
+    data: core::slice::Iter<'a, u64>,
+}
+
+impl<'a> Iterator for Units<'a> {
+    type Item = Char64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(|unit| *Char64::from_unit_ref(unit))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth(n).map(|unit| *Char64::from_unit_ref(unit))
+    }
+
+    fn count(self) -> usize {
+        self.data.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Units<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data
+            .next_back()
+            .map(|unit| *Char64::from_unit_ref(unit))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data
+            .nth_back(n)
+            .map(|unit| *Char64::from_unit_ref(unit))
+    }
+}
+
+impl<'a> ExactSizeIterator for Units<'a> {
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl<'a> FusedIterator for Units<'a> {}
+
+/// An iterator over the UTF-16 code units of a UTF64 string.
+///
+/// Created by [`Str64::encode_utf16`].
+#[derive(Clone)]
+pub struct EncodeUtf16<'a> {
+    data: core::slice::Iter<'a, u64>,
+    /// The low surrogate still owed for the last character.
+    low_surrogate: Option<u16>,
+}
+
+impl<'a> Iterator for EncodeUtf16<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(low) = self.low_surrogate.take() {
+            return Some(low);
+        }
+
+        let ch = decode_lossy(*self.data.next()?);
+        let mut buf = [0; 2];
+        let encoded = ch.encode_utf16(&mut buf);
+        if encoded.len() == 2 {
+            self.low_surrogate = Some(encoded[1]);
+        }
+        Some(encoded[0])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.low_surrogate.is_some() as usize;
+        let remaining = self.data.len();
+        (
+            remaining + pending,
+            remaining
+                .checked_mul(2)
+                .and_then(|n| n.checked_add(pending)),
+        )
+    }
+}
+
+impl<'a> FusedIterator for EncodeUtf16<'a> {}
@@ -1,12 +1,26 @@
-use crate::error::{Result, Utf64Error};
-use std::{
+use crate::byte_order::{ByteOrder, read_units};
+use crate::char64::Char64;
+use crate::error::{ErrorKind, FromUnitsError, Result, Utf64Error};
+#[cfg(feature = "std")]
+use crate::io::Utf8Tail;
+use crate::simd::encode_utf8;
+use crate::str64::Str64;
+use crate::unit::{REPLACEMENT_UNIT, decode_lossy, encode_char, validate_unit, validate_units};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
     borrow::{Borrow, BorrowMut},
     fmt,
     hash::{Hash, Hasher},
-    iter::{Extend, FromIterator},
-    ops::{Add, AddAssign, Deref, DerefMut, Index, Range, RangeFrom, RangeFull, RangeTo},
+    iter::{Extend, FromIterator, FusedIterator},
+    ops::{
+        Add, AddAssign, Deref, DerefMut, Index, Range, RangeBounds, RangeFrom, RangeFull, RangeTo,
+    },
     str::FromStr,
 };
+#[cfg(feature = "std")]
+use std::io;
+
+pub use crate::str64::{CharIndices, EncodeUtf16, Iter, TryChars, Units};
 
 /// A UTF64-encoded string.
 ///
@@ -21,110 +35,422 @@ use std::{
 /// # Examples
 ///
 /// ```
-/// use utf64::String64;
+/// use utf_64::String64;
 ///
 /// let s = String64::from("Hello, 世界!");
 /// assert_eq!(s.len(), 10); // 10 characters
 /// ```
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct String64 {
     data: Vec<u64>,
+    /// Incomplete UTF-8 sequence held back by the `io::Write` impl, boxed so that it
+    /// costs a single pointer while nothing is pending.
+    #[cfg(feature = "std")]
+    tail: Option<Box<Utf8Tail>>,
 }
 
 impl String64 {
     /// Creates a new empty `String64`.
     pub fn new() -> Self {
-        Self { data: Vec::new() }
+        Self::from_raw(Vec::new())
     }
 
     /// Creates a new `String64` with the specified capacity.
     pub fn with_capacity(capacity: usize) -> Self {
-        Self {
-            data: Vec::with_capacity(capacity),
+        Self::from_raw(Vec::with_capacity(capacity))
+    }
+
+    /// Creates a `String64` from a buffer of raw UTF64 units.
+    ///
+    /// Every unit is validated. On failure the buffer is handed back inside the
+    /// [`FromUnitsError`] so it is not lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from_units(vec![0x48000000_00000000, 0x69000000_00000000]).unwrap();
+    /// assert_eq!(s, "Hi");
+    ///
+    /// let err = String64::from_units(vec![0x48000000_00000000, 0x1]).unwrap_err();
+    /// assert_eq!(err.utf64_error().index(), 1);
+    /// assert_eq!(err.into_units(), vec![0x48000000_00000000, 0x1]);
+    /// ```
+    pub fn from_units(units: Vec<u64>) -> core::result::Result<Self, FromUnitsError> {
+        match validate_units(&units) {
+            Ok(()) => Ok(Self::from_raw(units)),
+            Err(error) => Err(FromUnitsError::new(units, error)),
         }
     }
 
-    /// Returns the length of this `String64` in characters.
+    /// Parses serialized UTF64 bytes in the given byte order.
     ///
-    /// Note: This is O(1) as each character is exactly one u64.
-    pub fn len(&self) -> usize {
-        self.data.len()
+    /// The length of `bytes` must be a multiple of 8 and every unit is validated. A
+    /// byte order mark is not treated specially; use
+    /// [`from_bytes_detect`](Self::from_bytes_detect) for input that may carry one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::{ByteOrder, String64};
+    ///
+    /// let bytes = [0, 0, 0, 0, 0, 0, 0, 0x41];
+    /// let s = String64::from_bytes(&bytes, ByteOrder::LittleEndian).unwrap();
+    /// assert_eq!(s, "A");
+    /// ```
+    pub fn from_bytes(bytes: &[u8], order: ByteOrder) -> Result<Self> {
+        let units = read_units(bytes, order)?;
+        validate_units(&units)?;
+        Ok(Self::from_raw(units))
     }
 
-    /// Returns `true` if this `String64` has a length of zero.
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+    /// Parses serialized UTF64 bytes, detecting the byte order from a leading BOM.
+    ///
+    /// If `bytes` starts with a byte order mark it selects the byte order and is
+    /// stripped; otherwise big-endian is assumed, as SPECIFICATION §3.4 prescribes.
+    /// The length of `bytes` must be a multiple of 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::{ByteOrder, String64};
+    ///
+    /// let original = String64::from("Hi🌍");
+    /// let bytes = original.to_bytes_with_bom(ByteOrder::LittleEndian);
+    /// assert_eq!(String64::from_bytes_detect(&bytes).unwrap(), original);
+    /// ```
+    pub fn from_bytes_detect(bytes: &[u8]) -> Result<Self> {
+        match ByteOrder::from_bom(bytes) {
+            Some(order) => Self::from_bytes(&bytes[8..], order),
+            None => Self::from_bytes(bytes, ByteOrder::BigEndian),
+        }
     }
 
-    /// Returns a slice of the underlying u64 data.
-    pub fn as_slice(&self) -> &[u64] {
-        &self.data
+    /// Transcodes UTF-16 into a `String64`.
+    ///
+    /// Surrogate pairs become a single unit. An unpaired surrogate fails with
+    /// [`ErrorKind::InvalidCodePoint`]; the error's [`index`](Utf64Error::index) is its
+    /// position in `v`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::{ErrorKind, String64};
+    ///
+    /// let v = [0x0048, 0x0069, 0xD83C, 0xDF0D]; // "Hi🌍"
+    /// assert_eq!(String64::from_utf16(&v).unwrap(), "Hi🌍");
+    ///
+    /// let err = String64::from_utf16(&[0x0048, 0xDC00]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidCodePoint(0xDC00));
+    /// assert_eq!(err.index(), 1);
+    /// ```
+    pub fn from_utf16(v: &[u16]) -> Result<Self> {
+        Self::decode_utf16(v.iter().copied(), v.len())
     }
 
-    /// Encodes a string slice into UTF64 format.
-    fn encode(s: &str) -> Result<Self> {
-        let mut data = Vec::with_capacity(s.chars().count());
+    /// Transcodes UTF-16 into a `String64`, replacing unpaired surrogates with U+FFFD.
+    pub fn from_utf16_lossy(v: &[u16]) -> Self {
+        Self::decode_utf16_lossy(v.iter().copied(), v.len())
+    }
 
-        for ch in s.chars() {
-            let mut utf8_buf = [0u8; 4];
-            let utf8_bytes = ch.encode_utf8(&mut utf8_buf).as_bytes();
+    /// Transcodes serialized UTF-16 bytes in the given byte order.
+    ///
+    /// No byte order mark is expected; a leading U+FEFF is kept as a character. Fails
+    /// as [`from_utf16`](Self::from_utf16) does, with indices counted in 16-bit code
+    /// units, or with [`ErrorKind::IncompleteUtf16`] if the length of `bytes` is odd.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::{ByteOrder, String64};
+    ///
+    /// let bytes = [0x48, 0x00, 0xAC, 0x20]; // "H€" in UTF-16LE
+    /// let s = String64::from_utf16_bytes(&bytes, ByteOrder::LittleEndian).unwrap();
+    /// assert_eq!(s, "H€");
+    /// ```
+    pub fn from_utf16_bytes(bytes: &[u8], order: ByteOrder) -> Result<Self> {
+        // An error in the whole code units comes first, so it wins over a trailing byte
+        let s = Self::decode_utf16(utf16_code_units(bytes, order), bytes.len() / 2)?;
+        if !bytes.len().is_multiple_of(2) {
+            return Err(Utf64Error::new(
+                ErrorKind::IncompleteUtf16,
+                bytes.len() / 2,
+                0,
+            ));
+        }
+        Ok(s)
+    }
 
-            // Pack UTF-8 bytes into upper 32 bits (big-endian style)
-            let mut upper_bits: u32 = 0;
-            for (i, &byte) in utf8_bytes.iter().enumerate() {
-                upper_bits |= (byte as u32) << (24 - (i * 8));
-            }
+    /// Transcodes serialized UTF-16 bytes in the given byte order, replacing unpaired
+    /// surrogates with U+FFFD.
+    ///
+    /// A trailing odd byte also becomes U+FFFD.
+    pub fn from_utf16_bytes_lossy(bytes: &[u8], order: ByteOrder) -> Self {
+        let mut s = Self::decode_utf16_lossy(utf16_code_units(bytes, order), bytes.len() / 2);
+        if !bytes.len().is_multiple_of(2) {
+            s.data.push(REPLACEMENT_UNIT);
+        }
+        s
+    }
+
+    /// Creates a `String64` from a buffer of raw UTF64 units without validating them.
+    ///
+    /// Skips the check [`from_units`](Self::from_units) makes, for units already known
+    /// to be valid. This is not `unsafe`: like any `String64`, one built from invalid
+    /// units decodes them as U+FFFD in the lossy APIs and reports them as errors in the
+    /// strict ones, such as [`to_string`](Str64::to_string).
+    pub fn from_units_unchecked(units: Vec<u64>) -> Self {
+        Self::from_raw(units)
+    }
+
+    /// Converts this `String64` into its buffer of raw UTF64 units.
+    ///
+    /// This does not copy or re-encode anything.
+    pub fn into_units(self) -> Vec<u64> {
+        self.data
+    }
+
+    /// Appends a character to the end of this string.
+    pub fn push(&mut self, ch: char) {
+        self.data_mut().push(encode_char(ch));
+    }
+
+    /// Appends a string slice to the end of this string.
+    pub fn push_str(&mut self, s: &str) {
+        encode_utf8(s, self.data_mut());
+    }
+
+    /// Removes the last character and returns it, or `None` if the string is empty.
+    ///
+    /// An invalid unit is returned as U+FFFD REPLACEMENT CHARACTER.
+    pub fn pop(&mut self) -> Option<char> {
+        self.data_mut().pop().map(decode_lossy)
+    }
+
+    /// Inserts a character at character index `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than the string's length.
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.data_mut().insert(idx, encode_char(ch));
+    }
+
+    /// Inserts a string slice at character index `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than the string's length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let mut s = String64::from("Hello!");
+    /// s.insert_str(5, ", 世界");
+    /// assert_eq!(s, "Hello, 世界!");
+    /// ```
+    pub fn insert_str(&mut self, idx: usize, s: &str) {
+        self.replace_range(idx..idx, s);
+    }
+
+    /// Removes the character at character index `idx` and returns it.
+    ///
+    /// An invalid unit is returned as U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn remove(&mut self, idx: usize) -> char {
+        decode_lossy(self.data_mut().remove(idx))
+    }
+
+    /// Shortens this string to `new_len` characters.
+    ///
+    /// Has no effect if `new_len` is greater than the string's current length.
+    pub fn truncate(&mut self, new_len: usize) {
+        self.data_mut().truncate(new_len);
+    }
+
+    /// Removes all characters, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.data_mut().clear();
+    }
+
+    /// Splits the string in two at character index `at`.
+    ///
+    /// Returns the characters from `at` onwards; `self` keeps the characters before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is larger than the string's length.
+    pub fn split_off(&mut self, at: usize) -> String64 {
+        Self::from_raw(self.data_mut().split_off(at))
+    }
+
+    /// Keeps only the characters for which `f` returns `true`.
+    ///
+    /// Invalid units are passed to `f` as U+FFFD REPLACEMENT CHARACTER.
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        self.data_mut().retain(|&unit| f(decode_lossy(unit)));
+    }
+
+    /// Removes the given character range and returns its characters as an iterator.
+    ///
+    /// The range is removed even if the iterator is not fully consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is after its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let mut s = String64::from("α is alpha");
+    /// let alpha: String = s.drain(..2).collect();
+    /// assert_eq!(alpha, "α ");
+    /// assert_eq!(s, "is alpha");
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_> {
+        Drain {
+            data: self.data_mut().drain(range),
+        }
+    }
+
+    /// Replaces the given character range with a string slice.
+    ///
+    /// The replacement does not need to have the same length as the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is after its end.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &str) {
+        let mut units = Vec::new();
+        encode_utf8(replace_with, &mut units);
+        self.data_mut().splice(range, units);
+    }
 
-            // Upper 32 bits = UTF-8, Lower 32 bits = reserved (0)
-            let utf64_char = (upper_bits as u64) << 32;
-            data.push(utf64_char);
+    /// Wraps a buffer of raw units regardless of their validity.
+    pub(crate) fn from_raw(units: Vec<u64>) -> Self {
+        Self {
+            data: units,
+            #[cfg(feature = "std")]
+            tail: None,
         }
+    }
 
-        Ok(Self { data })
+    /// Returns the buffer for a change made through anything but `io::Write`.
+    ///
+    /// Bytes held back by `io::Write` are dropped first, so that the rest of a split
+    /// sequence written afterwards is rejected rather than completing a character
+    /// across the change.
+    fn data_mut(&mut self) -> &mut Vec<u64> {
+        #[cfg(feature = "std")]
+        {
+            self.tail = None;
+        }
+        &mut self.data
+    }
+
+    /// Returns a `Str64` slice covering the whole string.
+    pub fn as_str64(&self) -> &Str64 {
+        Str64::from_raw(&self.data)
     }
 
     /// Decodes this UTF64 string back to a standard Rust String.
+    ///
+    /// See [`Str64::to_string`].
     pub fn to_string(&self) -> Result<String> {
-        let mut utf8_bytes = Vec::new();
+        self.as_str64().to_string()
+    }
 
-        for &utf64_char in &self.data {
-            // Check that reserved bits (lower 32) are zero
-            if (utf64_char & 0xFFFFFFFF) != 0 {
-                return Err(Utf64Error::NonZeroReservedBits);
-            }
+    /// Converts this `String64` into a `Box<Str64>`, dropping any excess capacity.
+    pub fn into_boxed_str64(self) -> Box<Str64> {
+        let units = self.data.into_boxed_slice();
+        // SAFETY: `Str64` is `repr(transparent)` over `[u64]`.
+        unsafe { Box::from_raw(Box::into_raw(units) as *mut Str64) }
+    }
 
-            // Extract upper 32 bits
-            let upper_bits = (utf64_char >> 32) as u32;
-
-            // Extract UTF-8 bytes (up to 4 bytes)
-            let bytes = [
-                ((upper_bits >> 24) & 0xFF) as u8,
-                ((upper_bits >> 16) & 0xFF) as u8,
-                ((upper_bits >> 8) & 0xFF) as u8,
-                (upper_bits & 0xFF) as u8,
-            ];
-
-            // Find the actual length of the UTF-8 sequence
-            // UTF-8 first byte tells us the length
-            let len = if bytes[0] == 0 {
-                return Err(Utf64Error::InvalidUtf64);
-            } else if bytes[0] < 0x80 {
-                1
-            } else if bytes[0] < 0xE0 {
-                2
-            } else if bytes[0] < 0xF0 {
-                3
-            } else {
-                4
-            };
-
-            utf8_bytes.extend_from_slice(&bytes[..len]);
+    /// Transcodes UTF-16 code units, failing at the first unpaired surrogate.
+    ///
+    /// `len` is the number of code units, an upper bound on the number of characters.
+    fn decode_utf16(code_units: impl Iterator<Item = u16>, len: usize) -> Result<Self> {
+        let mut data = Vec::with_capacity(len);
+        let mut position = 0;
+        for decoded in char::decode_utf16(code_units) {
+            match decoded {
+                Ok(ch) => {
+                    data.push(encode_char(ch));
+                    position += ch.len_utf16();
+                }
+                Err(err) => {
+                    let surrogate = err.unpaired_surrogate() as u32;
+                    return Err(Utf64Error::new(
+                        ErrorKind::InvalidCodePoint(surrogate),
+                        position,
+                        surrogate as u64,
+                    ));
+                }
+            }
         }
+        Ok(Self::from_raw(data))
+    }
 
-        String::from_utf8(utf8_bytes).map_err(|_| Utf64Error::InvalidUtf8)
+    /// Transcodes UTF-16 code units, replacing unpaired surrogates with U+FFFD.
+    fn decode_utf16_lossy(code_units: impl Iterator<Item = u16>, len: usize) -> Self {
+        let mut data = Vec::with_capacity(len);
+        data.extend(
+            char::decode_utf16(code_units)
+                .map(|decoded| decoded.map_or(REPLACEMENT_UNIT, encode_char)),
+        );
+        Self::from_raw(data)
+    }
+
+    /// Encodes a string slice into UTF64 format.
+    fn encode(s: &str) -> Result<Self> {
+        let mut data = Vec::new();
+        encode_utf8(s, &mut data);
+        Ok(Self::from_raw(data))
+    }
+
+    /// Creates a `String64` from raw UTF64 units, replacing invalid units with U+FFFD.
+    ///
+    /// The lossy counterpart of [`from_units`](Self::from_units). Invalid units are
+    /// overwritten in place, so the buffer is reused without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utf_64::String64;
+    ///
+    /// let s = String64::from_units_lossy(vec![0x48000000_00000000, 0xFF]);
+    /// assert_eq!(s, "H\u{FFFD}");
+    /// ```
+    pub fn from_units_lossy(mut units: Vec<u64>) -> Self {
+        for unit in &mut units {
+            if validate_unit(*unit).is_err() {
+                *unit = REPLACEMENT_UNIT;
+            }
+        }
+        Self::from_raw(units)
     }
 }
 
+/// Reads the whole 16-bit code units of serialized UTF-16, ignoring a trailing odd byte.
+fn utf16_code_units(bytes: &[u8], order: ByteOrder) -> impl Iterator<Item = u16> + '_ {
+    bytes.chunks_exact(2).map(move |pair| {
+        let pair = [pair[0], pair[1]];
+        match order {
+            ByteOrder::BigEndian => u16::from_be_bytes(pair),
+            ByteOrder::LittleEndian => u16::from_le_bytes(pair),
+        }
+    })
+}
+
 impl Default for String64 {
     fn default() -> Self {
         Self::new()
@@ -146,29 +472,31 @@ impl From<String> for String64 {
 impl FromStr for String64 {
     type Err = Utf64Error;
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
         Self::encode(s)
     }
 }
 
 impl fmt::Display for String64 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.to_string() {
-            Ok(s) => write!(f, "{s}"),
-            Err(_) => write!(f, "<invalid UTF64>"),
-        }
+        fmt::Display::fmt(self.as_str64(), f)
     }
 }
 
 impl fmt::Debug for String64 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.to_string() {
-            Ok(s) => write!(f, "String64({s:?})"),
-            Err(_) => write!(f, "String64(<invalid>)"),
-        }
+        self.as_str64().fmt_debug("String64", f)
+    }
+}
+
+impl PartialEq for String64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
     }
 }
 
+impl Eq for String64 {}
+
 impl Hash for String64 {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.data.hash(state);
@@ -176,103 +504,96 @@ impl Hash for String64 {
 }
 
 impl PartialOrd for String64 {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
 impl Ord for String64 {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        // Lexicographic comparison by decoding to strings
-        match (self.to_string(), other.to_string()) {
-            (Ok(s1), Ok(s2)) => s1.cmp(&s2),
-            (Ok(_), Err(_)) => std::cmp::Ordering::Greater,
-            (Err(_), Ok(_)) => std::cmp::Ordering::Less,
-            (Err(_), Err(_)) => std::cmp::Ordering::Equal,
-        }
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str64().cmp(other.as_str64())
     }
 }
 
 impl Index<usize> for String64 {
-    type Output = u64;
+    type Output = Char64;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index]
+        &self.as_str64()[index]
     }
 }
 
 impl Index<Range<usize>> for String64 {
-    type Output = [u64];
+    type Output = Str64;
 
     fn index(&self, range: Range<usize>) -> &Self::Output {
-        &self.data[range]
+        &self.as_str64()[range]
     }
 }
 
 impl Index<RangeFrom<usize>> for String64 {
-    type Output = [u64];
+    type Output = Str64;
 
     fn index(&self, range: RangeFrom<usize>) -> &Self::Output {
-        &self.data[range]
+        &self.as_str64()[range]
     }
 }
 
 impl Index<RangeTo<usize>> for String64 {
-    type Output = [u64];
+    type Output = Str64;
 
     fn index(&self, range: RangeTo<usize>) -> &Self::Output {
-        &self.data[range]
+        &self.as_str64()[range]
     }
 }
 
 impl Index<RangeFull> for String64 {
-    type Output = [u64];
+    type Output = Str64;
 
-    fn index(&self, range: RangeFull) -> &Self::Output {
-        &self.data[range]
+    fn index(&self, _range: RangeFull) -> &Self::Output {
+        self.as_str64()
     }
 }
 
 /// Iterator that yields characters from a String64 by consuming it.
+///
+/// Units that fail validation are yielded as U+FFFD REPLACEMENT CHARACTER.
 pub struct IntoIter {
-    data: std::vec::IntoIter<u64>,
+    data: alloc::vec::IntoIter<u64>,
 }
 
 impl Iterator for IntoIter {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.data.next().map(|utf64_char| {
-            // Extract upper 32 bits and decode the UTF-8
-            let upper_bits = (utf64_char >> 32) as u32;
-            let bytes = [
-                ((upper_bits >> 24) & 0xFF) as u8,
-                ((upper_bits >> 16) & 0xFF) as u8,
-                ((upper_bits >> 8) & 0xFF) as u8,
-                (upper_bits & 0xFF) as u8,
-            ];
-
-            // Determine UTF-8 length and decode
-            let len = if bytes[0] < 0x80 {
-                1
-            } else if bytes[0] < 0xE0 {
-                2
-            } else if bytes[0] < 0xF0 {
-                3
-            } else {
-                4
-            };
-
-            std::str::from_utf8(&bytes[..len])
-                .ok()
-                .and_then(|s| s.chars().next())
-                .expect("valid UTF64 should decode to valid char")
-        })
+        self.data.next().map(decode_lossy)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.data.size_hint()
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth(n).map(decode_lossy)
+    }
+
+    fn count(self) -> usize {
+        self.data.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back().map(decode_lossy)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.data.nth_back(n).map(decode_lossy)
+    }
 }
 
 impl ExactSizeIterator for IntoIter {
@@ -281,6 +602,8 @@ impl ExactSizeIterator for IntoIter {
     }
 }
 
+impl FusedIterator for IntoIter {}
+
 impl IntoIterator for String64 {
     type Item = char;
     type IntoIter = IntoIter;
@@ -292,41 +615,28 @@ impl IntoIterator for String64 {
     }
 }
 
-/// Iterator that yields characters from a &String64 without consuming it.
-pub struct Iter<'a> {
-    data: std::slice::Iter<'a, u64>,
+impl<'a> IntoIterator for &'a String64 {
+    type Item = char;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chars_lossy()
+    }
 }
 
-impl<'a> Iterator for Iter<'a> {
+/// A draining iterator over the characters of a String64.
+///
+/// Created by [`String64::drain`]. Units that fail validation are yielded as U+FFFD
+/// REPLACEMENT CHARACTER.
+pub struct Drain<'a> {
+    data: alloc::vec::Drain<'a, u64>,
+}
+
+impl<'a> Iterator for Drain<'a> {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.data.next().map(|&utf64_char| {
-            // Extract upper 32 bits and decode the UTF-8
-            let upper_bits = (utf64_char >> 32) as u32;
-            let bytes = [
-                ((upper_bits >> 24) & 0xFF) as u8,
-                ((upper_bits >> 16) & 0xFF) as u8,
-                ((upper_bits >> 8) & 0xFF) as u8,
-                (upper_bits & 0xFF) as u8,
-            ];
-
-            // Determine UTF-8 length and decode
-            let len = if bytes[0] < 0x80 {
-                1
-            } else if bytes[0] < 0xE0 {
-                2
-            } else if bytes[0] < 0xF0 {
-                3
-            } else {
-                4
-            };
-
-            std::str::from_utf8(&bytes[..len])
-                .ok()
-                .and_then(|s| s.chars().next())
-                .expect("valid UTF64 should decode to valid char")
-        })
+        self.data.next().map(decode_lossy)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -334,23 +644,20 @@ impl<'a> Iterator for Iter<'a> {
     }
 }
 
-impl<'a> ExactSizeIterator for Iter<'a> {
-    fn len(&self) -> usize {
-        self.data.len()
+impl<'a> DoubleEndedIterator for Drain<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.data.next_back().map(decode_lossy)
     }
 }
 
-impl<'a> IntoIterator for &'a String64 {
-    type Item = char;
-    type IntoIter = Iter<'a>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        Iter {
-            data: self.data.iter(),
-        }
+impl<'a> ExactSizeIterator for Drain<'a> {
+    fn len(&self) -> usize {
+        self.data.len()
     }
 }
 
+impl<'a> FusedIterator for Drain<'a> {}
+
 impl FromIterator<char> for String64 {
     fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
         let mut s = String64::new();
@@ -361,19 +668,7 @@ impl FromIterator<char> for String64 {
 
 impl Extend<char> for String64 {
     fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
-        for ch in iter {
-            let mut utf8_buf = [0u8; 4];
-            let utf8_bytes = ch.encode_utf8(&mut utf8_buf).as_bytes();
-
-            // Pack UTF-8 bytes into upper 32 bits
-            let mut upper_bits: u32 = 0;
-            for (i, &byte) in utf8_bytes.iter().enumerate() {
-                upper_bits |= (byte as u32) << (24 - (i * 8));
-            }
-
-            let utf64_char = (upper_bits as u64) << 32;
-            self.data.push(utf64_char);
-        }
+        self.data_mut().extend(iter.into_iter().map(encode_char));
     }
 }
 
@@ -381,23 +676,74 @@ impl Add<&str> for String64 {
     type Output = String64;
 
     fn add(mut self, rhs: &str) -> Self::Output {
-        self.extend(rhs.chars());
+        self.push_str(rhs);
         self
     }
 }
 
 impl AddAssign<&str> for String64 {
     fn add_assign(&mut self, rhs: &str) {
-        self.extend(rhs.chars());
+        self.push_str(rhs);
+    }
+}
+
+impl fmt::Write for String64 {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    fn write_char(&mut self, ch: char) -> fmt::Result {
+        self.push(ch);
+        Ok(())
+    }
+}
+
+/// Appends UTF-8 bytes to the string.
+///
+/// A multi-byte sequence split across calls is held back until the rest of it
+/// arrives; it is not part of the string until then, so comparisons, hashing and
+/// [`into_units`](String64::into_units) do not see it. Any other change to the string
+/// drops the held-back bytes. Invalid UTF-8 is reported as an
+/// [`io::ErrorKind::InvalidData`] error, and [`flush`](io::Write::flush) fails while
+/// an incomplete sequence is held back.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use utf_64::String64;
+///
+/// let mut s = String64::new();
+/// s.write_all(&[0x48, 0xE2, 0x82]).unwrap(); // 'H' and the start of '€'
+/// assert_eq!(s, "H");
+/// s.write_all(&[0xAC]).unwrap();
+/// assert_eq!(s, "H€");
+/// ```
+#[cfg(feature = "std")]
+impl io::Write for String64 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Self { data, tail } = self;
+        let mut pending = tail.as_deref().copied().unwrap_or_default();
+        let written = pending.feed(buf, |ch| data.push(encode_char(ch)));
+        *tail = pending.is_pending().then(|| Box::new(pending));
+        written
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.tail.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete UTF-8 sequence",
+            ));
+        }
+        Ok(())
     }
 }
 
 impl PartialEq<str> for String64 {
     fn eq(&self, other: &str) -> bool {
-        match self.to_string() {
-            Ok(s) => s == other,
-            Err(_) => false,
-        }
+        self.as_str64() == other
     }
 }
 
@@ -413,12 +759,39 @@ impl PartialEq<String> for String64 {
     }
 }
 
+impl PartialEq<Str64> for String64 {
+    fn eq(&self, other: &Str64) -> bool {
+        self.as_str64() == other
+    }
+}
+
+impl PartialEq<&Str64> for String64 {
+    fn eq(&self, other: &&Str64) -> bool {
+        self.as_str64() == *other
+    }
+}
+
 impl AsRef<[u64]> for String64 {
     fn as_ref(&self) -> &[u64] {
         &self.data
     }
 }
 
+impl AsRef<Str64> for String64 {
+    fn as_ref(&self) -> &Str64 {
+        self.as_str64()
+    }
+}
+
+impl TryFrom<&[u64]> for String64 {
+    type Error = Utf64Error;
+
+    fn try_from(units: &[u64]) -> Result<Self> {
+        validate_units(units)?;
+        Ok(Self::from_raw(units.to_vec()))
+    }
+}
+
 impl TryFrom<String64> for String {
     type Error = Utf64Error;
 
@@ -436,16 +809,28 @@ impl TryFrom<&String64> for String {
 }
 
 impl Deref for String64 {
-    type Target = [u64];
+    type Target = Str64;
 
     fn deref(&self) -> &Self::Target {
-        &self.data
+        self.as_str64()
     }
 }
 
 impl DerefMut for String64 {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.data
+        Str64::from_raw_mut(self.data_mut())
+    }
+}
+
+impl Borrow<Str64> for String64 {
+    fn borrow(&self) -> &Str64 {
+        self.as_str64()
+    }
+}
+
+impl BorrowMut<Str64> for String64 {
+    fn borrow_mut(&mut self) -> &mut Str64 {
+        self
     }
 }
 
@@ -457,6 +842,6 @@ impl Borrow<[u64]> for String64 {
 
 impl BorrowMut<[u64]> for String64 {
     fn borrow_mut(&mut self) -> &mut [u64] {
-        &mut self.data
+        self.data_mut()
     }
 }
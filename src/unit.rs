@@ -0,0 +1,192 @@
+//! Encoding and decoding of individual UTF64 character units.
+//!
+//! Every decoding path in the crate goes through the same per-unit validator, so the
+//! rules from SPECIFICATION §5–6 are enforced in exactly one place.
+
+use crate::error::{ErrorKind, Result, Utf64Error};
+use crate::simd;
+
+/// Mask selecting the reserved lower half of a UTF64 unit.
+pub(crate) const RESERVED_MASK: u64 = 0xFFFF_FFFF;
+
+/// The UTF64 unit for U+FFFD REPLACEMENT CHARACTER, substituted for invalid units.
+#[cfg(feature = "alloc")]
+pub(crate) const REPLACEMENT_UNIT: u64 = 0xEFBFBD00_00000000;
+
+/// Smallest code point that may be encoded with a UTF-8 sequence of each length.
+///
+/// Indexed by sequence length; anything below the minimum is an overlong encoding.
+const MIN_CODE_POINT: [u32; 5] = [0, 0, 0x80, 0x800, 0x1_0000];
+
+/// Encodes a Unicode code point into a single UTF64 unit.
+///
+/// Implements `encode_utf64` from SPECIFICATION §4.2. Surrogates (U+D800 to U+DFFF)
+/// and values above U+10FFFF are rejected with [`ErrorKind::InvalidCodePoint`]; the
+/// error's [`unit`](Utf64Error::unit) then holds the rejected code point.
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::encode_code_point;
+///
+/// assert_eq!(encode_code_point(0x20AC), Ok(0xE282AC00_00000000));
+/// assert!(encode_code_point(0xD800).is_err());
+/// assert!(encode_code_point(0x110000).is_err());
+/// ```
+pub fn encode_code_point(code_point: u32) -> Result<u64> {
+    char::from_u32(code_point).map(encode_char).ok_or_else(|| {
+        Utf64Error::new(
+            ErrorKind::InvalidCodePoint(code_point),
+            0,
+            code_point as u64,
+        )
+    })
+}
+
+/// Decodes a single UTF64 unit into the character it encodes.
+///
+/// The inverse of [`encode_code_point`] for every code point it accepts, U+0000
+/// included. The unit is fully validated; on failure the returned error has index 0.
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::{decode_unit, ErrorKind};
+///
+/// assert_eq!(decode_unit(0xF09F9880_00000000), Ok('😀'));
+/// assert_eq!(decode_unit(0), Ok('\0'));
+/// assert_eq!(
+///     decode_unit(0x41000000_00000001).unwrap_err().kind(),
+///     ErrorKind::NonZeroReserved
+/// );
+/// ```
+pub fn decode_unit(unit: u64) -> Result<char> {
+    validate_unit(unit).map_err(|kind| Utf64Error::new(kind, 0, unit))
+}
+
+/// Encodes a `char` into a UTF64 unit.
+///
+/// Infallible because every `char` is a Unicode scalar value.
+pub(crate) fn encode_char(ch: char) -> u64 {
+    let mut utf8_buf = [0u8; 4];
+    let utf8_bytes = ch.encode_utf8(&mut utf8_buf).as_bytes();
+
+    // Pack UTF-8 bytes into upper 32 bits (big-endian style)
+    let mut upper_bits: u32 = 0;
+    for (i, &byte) in utf8_bytes.iter().enumerate() {
+        upper_bits |= (byte as u32) << (24 - (i * 8));
+    }
+
+    // Upper 32 bits = UTF-8, Lower 32 bits = reserved (0)
+    (upper_bits as u64) << 32
+}
+
+/// Returns the length of the UTF-8 sequence introduced by a lead byte of valid UTF-8.
+#[cfg(feature = "alloc")]
+pub(crate) fn utf8_sequence_len(lead: u8) -> usize {
+    match lead {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        _ => 4,
+    }
+}
+
+/// Checks that every unit in `units` is valid UTF64 v1.0, without decoding anything.
+///
+/// Applies the same rules as [`decode_unit`], reporting the first invalid unit with its
+/// index. Runs of ASCII units are checked a block at a time with SIMD where the CPU
+/// supports it.
+///
+/// # Examples
+///
+/// ```
+/// use utf_64::{ErrorKind, String64, validate_units};
+///
+/// let s = String64::from("plenty of plain ASCII before the ünit");
+/// assert!(validate_units(s.as_slice()).is_ok());
+///
+/// let mut units = s.into_units();
+/// units[33] |= 1;
+/// let err = validate_units(&units).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::NonZeroReserved);
+/// assert_eq!(err.index(), 33);
+/// ```
+pub fn validate_units(units: &[u64]) -> Result<()> {
+    simd::validate(units)
+}
+
+/// Decodes a UTF64 unit, substituting U+FFFD REPLACEMENT CHARACTER if it is invalid.
+pub(crate) fn decode_lossy(unit: u64) -> char {
+    validate_unit(unit).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// Applies a byte mapping to a unit encoding an ASCII character.
+///
+/// Any other unit, including an invalid one, is returned unchanged, so the mapping
+/// never needs to decode.
+pub(crate) fn map_ascii(unit: u64, map: impl Fn(u8) -> u8) -> u64 {
+    let lead = (unit >> 56) as u8;
+    if lead.is_ascii() && unit << 8 == 0 {
+        (map(lead) as u64) << 56
+    } else {
+        unit
+    }
+}
+
+/// Validates a single UTF64 unit and returns the character it encodes.
+///
+/// The unit must satisfy every v1.0 decoding requirement:
+///
+/// - the reserved lower half is zero,
+/// - the first octet is non-zero, except in the all-zero unit, which encodes U+0000,
+/// - the upper half holds a well-formed RFC 3629 sequence (valid lead byte, valid
+///   continuation bytes, no overlong form, no surrogate, at most U+10FFFF),
+/// - every octet after the sequence is zero padding.
+///
+/// Callers attach the unit's position when turning the [`ErrorKind`] into a
+/// [`Utf64Error`](crate::Utf64Error).
+pub(crate) fn validate_unit(unit: u64) -> core::result::Result<char, ErrorKind> {
+    if unit & RESERVED_MASK != 0 {
+        return Err(ErrorKind::NonZeroReserved);
+    }
+
+    let bytes = ((unit >> 32) as u32).to_be_bytes();
+
+    let (len, mut code_point) = match bytes[0] {
+        // Only the all-zero unit may start with a zero octet: it is how every encoder
+        // writes U+0000, so accepting it keeps `&str` input round-tripping
+        0x00 if unit == 0 => return Ok('\0'),
+        0x00 => return Err(ErrorKind::ZeroLeadByte),
+        0x01..=0x7F => (1, bytes[0] as u32),
+        0xC0..=0xDF => (2, (bytes[0] & 0x1F) as u32),
+        0xE0..=0xEF => (3, (bytes[0] & 0x0F) as u32),
+        0xF0..=0xF7 => (4, (bytes[0] & 0x07) as u32),
+        // Stray continuation byte or a lead byte that RFC 3629 never allows
+        lead => return Err(ErrorKind::InvalidLeadByte(lead)),
+    };
+
+    for (octet, &byte) in bytes.iter().enumerate().take(len).skip(1) {
+        if byte & 0xC0 != 0x80 {
+            return Err(ErrorKind::BadContinuation {
+                octet: octet as u8,
+                found: byte,
+            });
+        }
+        code_point = (code_point << 6) | (byte & 0x3F) as u32;
+    }
+
+    if bytes[len..].iter().any(|&byte| byte != 0) {
+        return Err(ErrorKind::NonZeroPadding { len: len as u8 });
+    }
+
+    if code_point < MIN_CODE_POINT[len] {
+        return Err(ErrorKind::Overlong(code_point));
+    }
+
+    match code_point {
+        0xD800..=0xDFFF => Err(ErrorKind::Surrogate(code_point)),
+        0x11_0000.. => Err(ErrorKind::OutOfRange(code_point)),
+        _ => Ok(char::from_u32(code_point).expect("scalar value checked above")),
+    }
+}